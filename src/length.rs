@@ -9,13 +9,14 @@
 //! A one-dimensional length, tagged with its units.
 
 use scale_factor::ScaleFactor;
-use num::Zero;
+use num::{One, Zero};
 
 use num_lib::NumCast;
 #[cfg(feature = "plugins")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
-use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, Div, Neg};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, Div, Neg, RangeInclusive};
 use std::marker::PhantomData;
 
 /// A one-dimensional distance, with value represented by `T` and unit of measurement `Unit`.
@@ -62,6 +63,15 @@ impl<Unit, T: Clone> Length<Unit, T> {
     }
 }
 
+impl<Src, T: Clone + Mul<T, Output=T>> Length<Src, T> {
+    /// Converts this length to the units `Dst`, the same as `self * scale` but reading from
+    /// left to right.
+    #[inline]
+    pub fn into_space<Dst>(self, scale: ScaleFactor<Src, Dst, T>) -> Length<Dst, T> {
+        self * scale
+    }
+}
+
 // length + length
 impl<U, T: Clone + Add<T, Output=T>> Add for Length<U, T> {
     type Output = Length<U, T>;
@@ -77,6 +87,13 @@ impl<U, T: Clone + AddAssign<T>> AddAssign for Length<U, T> {
     }
 }
 
+// sum of lengths
+impl<U, T: Clone + Add<T, Output=T> + Zero> Sum for Length<U, T> {
+    fn sum<I: Iterator<Item=Length<U, T>>>(iter: I) -> Length<U, T> {
+        iter.fold(Zero::zero(), Add::add)
+    }
+}
+
 // length - length
 impl<U, T: Clone + Sub<T, Output=T>> Sub<Length<U, T>> for Length<U, T> {
     type Output = Length<U, T>;
@@ -135,6 +152,32 @@ impl<Unit, T0: NumCast + Clone> Length<Unit, T0> {
     }
 }
 
+// Convenience functions for common casts
+impl<Unit, T: NumCast + Clone> Length<Unit, T> {
+    pub fn as_f32(&self) -> Length<Unit, f32> {
+        self.cast().unwrap()
+    }
+
+    pub fn as_f64(&self) -> Length<Unit, f64> {
+        self.cast().unwrap()
+    }
+}
+
+impl<Unit, T: Clone + PartialOrd> Length<Unit, T> {
+    /// Returns this length clamped into `range`, e.g. `width.clamp_to(min_width..=max_width)`.
+    /// If the range is empty, its start wins.
+    pub fn clamp_to(self, range: RangeInclusive<Length<Unit, T>>) -> Length<Unit, T> {
+        let (start, end) = range.into_inner();
+        if self < start || start > end {
+            start
+        } else if self > end {
+            end
+        } else {
+            self
+        }
+    }
+}
+
 // FIXME: Switch to `derive(Clone, PartialEq, PartialOrd, Zero)` after this Rust issue is fixed:
 // https://github.com/mozilla/rust/issues/7671
 
@@ -166,15 +209,25 @@ impl<Unit, T: Zero> Zero for Length<Unit, T> {
     }
 }
 
+impl<Unit, T: One> One for Length<Unit, T> {
+    fn one() -> Length<Unit, T> {
+        Length::new(One::one())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Length;
+    use num::{One, Zero};
     use scale_factor::ScaleFactor;
+    use std::ops::Add;
 
     #[derive(Debug, Copy, Clone)]
     enum Inch {}
     #[derive(Debug, Copy, Clone)]
     enum Mm {}
+    #[derive(Debug, Copy, Clone)]
+    enum Cm {}
 
     #[test]
     fn test_length() {
@@ -223,6 +276,23 @@ mod tests {
         assert_eq!(negative_zero_feet.get(), 0.0);
     }
 
+    #[test]
+    fn test_into_space() {
+        let inch_per_cm: ScaleFactor<Cm, Inch, f32> = ScaleFactor::new(1.0 / 2.54);
+        let width: Length<Cm, f32> = Length::new(12.7);
+
+        let width_in_inches: Length<Inch, f32> = width.into_space(inch_per_cm);
+        assert!((width_in_inches.get() - 5.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_as_float() {
+        let width: Length<Mm, i32> = Length::new(42);
+
+        assert!(width.as_f32().get() == 42.0f32);
+        assert!(width.as_f64().get() == 42.0f64);
+    }
+
     #[test]
     fn test_addassign() {
         let one_cm: Length<Mm, f32> = Length::new(10.0);
@@ -233,6 +303,15 @@ mod tests {
         assert_eq!(measurement.get(), 15.0);
     }
 
+    #[test]
+    fn test_sum() {
+        let widths: Vec<Length<Mm, f32>> = vec![Length::new(10.0), Length::new(2.5),
+                                                Length::new(7.5)];
+        let total: Length<Mm, f32> = widths.iter().cloned().sum();
+
+        assert!(total.get() == 20.0);
+    }
+
     #[test]
     fn test_subassign() {
         let one_cm: Length<Mm, f32> = Length::new(10.0);
@@ -242,4 +321,45 @@ mod tests {
 
         assert_eq!(measurement.get(), -5.0);
     }
+
+    #[test]
+    fn test_clamp_to() {
+        let min_width: Length<Mm, f32> = Length::new(10.0);
+        let max_width: Length<Mm, f32> = Length::new(20.0);
+        let range = || min_width.clone()..=max_width.clone();
+
+        assert!(Length::new(5.0).clamp_to(range()) == min_width);
+        assert!(Length::new(15.0).clamp_to(range()).get() == 15.0);
+        assert!(Length::new(25.0).clamp_to(range()) == max_width);
+        assert!(Length::new(20.0).clamp_to(range()) == max_width);
+
+        // An empty range clamps everything to its start.
+        let empty = || max_width.clone()..=min_width.clone();
+        assert!(Length::new(25.0).clamp_to(empty()) == max_width);
+        assert!(Length::new(15.0).clamp_to(empty()) == max_width);
+        assert!(Length::new(5.0).clamp_to(empty()) == max_width);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut widths: Vec<Length<Mm, i32>> = vec![Length::new(30), Length::new(-5),
+                                                    Length::new(12)];
+        widths.sort();
+        assert!(widths == vec![Length::new(-5), Length::new(12), Length::new(30)]);
+        assert!(widths.iter().cloned().max() == Some(Length::new(30)));
+    }
+
+    #[test]
+    fn test_zero_one() {
+        fn total<T: Zero + Add<T, Output=T>>(values: Vec<T>) -> T {
+            values.into_iter().fold(Zero::zero(), |a, b| a + b)
+        }
+
+        let widths: Vec<Length<Mm, f32>> = vec![Length::new(1.5), Length::new(2.5)];
+        assert!(total(widths).get() == 4.0);
+        assert!(total(Vec::<Length<Mm, i32>>::new()) == Length::new(0));
+
+        let one: Length<Mm, i32> = One::one();
+        assert!(one.get() == 1);
+    }
 }
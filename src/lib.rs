@@ -23,12 +23,14 @@ extern crate log;
 extern crate rustc_serialize;
 #[cfg(feature = "plugins")]
 extern crate serde;
+#[cfg(all(test, feature = "plugins"))]
+extern crate serde_json;
 
 extern crate rand;
 extern crate test;
 extern crate num as num_lib;
 
-pub use matrix::Matrix4;
+pub use matrix::{Handedness, Matrix4};
 pub use matrix2d::Matrix2D;
 pub use point::{Point2D, Point3D, Point4D};
 pub use rect::Rect;
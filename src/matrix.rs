@@ -8,9 +8,18 @@
 // except according to those terms.
 
 use approxeq::ApproxEq;
-use point::{Point2D, Point4D};
+use matrix2d::Matrix2D;
+use point::{Point2D, Point3D, Point4D};
+use rect::Rect;
+use size::Size2D;
+use std::ops::{Add, Mul, MulAssign, Sub};
 
 
+/// A 4x4 transform, stored in row-vector convention.
+///
+/// `==` compares entries exactly. Matrices computed by different routes rarely agree to the
+/// last bit, so compare those with `approx_eq`, and use `exactly_eq` where an exact match is
+/// really intended.
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "plugins", derive(HeapSizeOf, Deserialize, Serialize))]
 pub struct Matrix4 {
@@ -20,6 +29,14 @@ pub struct Matrix4 {
     pub m41: f32, pub m42: f32, pub m43: f32, pub m44: f32,
 }
 
+/// The orientation of the coordinate system a transform produces, as reported by
+/// `Matrix4::handedness`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Handedness {
+    Left,
+    Right,
+}
+
 impl Matrix4 {
     pub fn new(
             m11: f32, m12: f32, m13: f32, m14: f32,
@@ -63,6 +80,29 @@ impl Matrix4 {
                      1.0)
     }
 
+    /// Creates an orthographic projection for pixel coordinates with the origin at the top
+    /// left and y pointing down, as is usual for 2d UI: `(0, 0)` maps to `(-1, 1)` in
+    /// normalized device coordinates and `(width, height)` to `(1, -1)`. Depth is mapped as by
+    /// `ortho` with `near = -1` and `far = 1`.
+    pub fn create_screen_ortho(width: f32, height: f32) -> Matrix4 {
+        Matrix4::ortho(0.0, width, height, 0.0, -1.0, 1.0)
+    }
+
+    /// Creates the viewport transform that `glViewport` and `glDepthRange` apply, mapping
+    /// normalized device coordinates in `[-1, 1]` to window coordinates: `(-1, -1, -1)` goes
+    /// to `(x, y, near)` and `(1, 1, 1)` to `(x + width, y + height, far)`.
+    ///
+    /// Like GL, y is not flipped, so for a top-left window origin pass a negative `height`
+    /// with `y` at the bottom edge.
+    pub fn create_viewport(x: f32, y: f32, width: f32, height: f32, near: f32, far: f32)
+                           -> Matrix4 {
+        let (half_width, half_height, half_depth) = (width / 2.0, height / 2.0, (far - near) / 2.0);
+        Matrix4::new(half_width, 0.0, 0.0, 0.0,
+                     0.0, half_height, 0.0, 0.0,
+                     0.0, 0.0, half_depth, 0.0,
+                     x + half_width, y + half_height, near + half_depth, 1.0)
+    }
+
     pub fn identity() -> Matrix4 {
         Matrix4::new(1.0, 0.0, 0.0, 0.0,
                      0.0, 1.0, 0.0, 0.0,
@@ -70,6 +110,43 @@ impl Matrix4 {
                      0.0, 0.0, 0.0, 1.0)
     }
 
+    /// Creates a matrix with `d.x`, `d.y`, `d.z` and `d.w` on the diagonal and zeros
+    /// elsewhere, which scales each component of a point independently, including `w`.
+    pub fn from_diagonal(d: Point4D<f32>) -> Matrix4 {
+        Matrix4::new(d.x, 0.0, 0.0, 0.0,
+                     0.0, d.y, 0.0, 0.0,
+                     0.0, 0.0, d.z, 0.0,
+                     0.0, 0.0, 0.0, d.w)
+    }
+
+    /// Returns true if this matrix has no perspective component, i.e. `m14`, `m24` and `m34`
+    /// are zero and `m44` is one.
+    ///
+    /// The comparison is exact. A matrix built up from affine transforms can pick up float
+    /// noise in those entries, in which case compare them with `approx_eq` instead.
+    #[inline]
+    pub fn is_affine(&self) -> bool {
+        self.m14 == 0.0 && self.m24 == 0.0 && self.m34 == 0.0 && self.m44 == 1.0
+    }
+
+    /// Returns true if this matrix is a 2d transform, one that can be written as a CSS
+    /// `matrix()`: every entry involving z or perspective is that of the identity.
+    /// https://drafts.csswg.org/css-transforms/#2d-matrix
+    #[inline]
+    pub fn is_2d(&self) -> bool {
+        self.m13 == 0.0 && self.m14 == 0.0 &&
+        self.m23 == 0.0 && self.m24 == 0.0 &&
+        self.m31 == 0.0 && self.m32 == 0.0 && self.m33 == 1.0 && self.m34 == 0.0 &&
+        self.m43 == 0.0 && self.m44 == 1.0
+    }
+
+    /// Returns true if every entry is exactly equal, the same as `==`. This is spelled out
+    /// so exact comparisons read as deliberate; computed matrices usually want `approx_eq`.
+    #[inline]
+    pub fn exactly_eq(&self, other: &Matrix4) -> bool {
+        *self == *other
+    }
+
     pub fn approx_eq(&self, other: &Matrix4) -> bool {
         self.m11.approx_eq(&other.m11) && self.m12.approx_eq(&other.m12) &&
         self.m13.approx_eq(&other.m13) && self.m14.approx_eq(&other.m14) &&
@@ -81,6 +158,36 @@ impl Matrix4 {
         self.m43.approx_eq(&other.m43) && self.m44.approx_eq(&other.m44)
     }
 
+    /// Like `approx_eq`, but only compares the six entries of the 2d affine transform (m11,
+    /// m12, m21, m22, m41 and m42), ignoring anything that only affects z or perspective.
+    pub fn approx_eq_2d(&self, other: &Matrix4) -> bool {
+        self.m11.approx_eq(&other.m11) && self.m12.approx_eq(&other.m12) &&
+        self.m21.approx_eq(&other.m21) && self.m22.approx_eq(&other.m22) &&
+        self.m41.approx_eq(&other.m41) && self.m42.approx_eq(&other.m42)
+    }
+
+    /// Returns the largest absolute difference between corresponding entries of two matrices.
+    /// Useful in tests to report how far apart two matrices are, rather than just whether they
+    /// are `approx_eq`.
+    pub fn max_abs_diff(&self, other: &Matrix4) -> f32 {
+        self.to_array().iter().zip(other.to_array().iter())
+                              .map(|(a, b)| (a - b).abs())
+                              .fold(0.0, f32::max)
+    }
+
+    /// Returns, for each entry in row-major order (`m11, m12, ..., m44`, as in `to_array`),
+    /// whether it differs from the entry in `other` by more than `eps`. Pinpoints which
+    /// entries make an `approx_eq` check fail.
+    pub fn diff_mask(&self, other: &Matrix4, eps: f32) -> [bool; 16] {
+        let (a, b) = (self.to_array(), other.to_array());
+        let mut mask = [false; 16];
+        for (differs, (x, y)) in mask.iter_mut().zip(a.iter().zip(b.iter())) {
+            *differs = (x - y).abs() > eps;
+        }
+        mask
+    }
+
+    #[inline]
     pub fn mul(&self, m: &Matrix4) -> Matrix4 {
         Matrix4::new(m.m11*self.m11 + m.m12*self.m21 + m.m13*self.m31 + m.m14*self.m41,
                      m.m11*self.m12 + m.m12*self.m22 + m.m13*self.m32 + m.m14*self.m42,
@@ -100,79 +207,70 @@ impl Matrix4 {
                      m.m41*self.m14 + m.m42*self.m24 + m.m43*self.m34 + m.m44*self.m44)
     }
 
+    /// Composes `other` into this matrix in place. Equivalent to `*self = self.mul(other)`.
+    #[inline]
+    pub fn mul_assign(&mut self, other: &Matrix4) {
+        *self = self.mul(other);
+    }
+
+    /// Composes a 2d transform on the left: `m.to_matrix4().mul(self)`. Points are
+    /// transformed by `self` before `m`.
+    #[inline]
+    pub fn pre_mul_2d(&self, m: &Matrix2D<f32>) -> Matrix4 {
+        m.to_matrix4().mul(self)
+    }
+
+    /// Composes a 2d transform on the right: `self.mul(&m.to_matrix4())`. Points are
+    /// transformed by `m` before `self`.
+    #[inline]
+    pub fn post_mul_2d(&self, m: &Matrix2D<f32>) -> Matrix4 {
+        self.mul(&m.to_matrix4())
+    }
+
     pub fn invert(&self) -> Matrix4 {
-        let det = self.determinant();
+        // The 2x2 minors of the top two and bottom two rows. Each cofactor, and the
+        // determinant itself, is a Laplace expansion over these, so they are computed once
+        // and shared: 94 multiplies in all, rather than the 280 it takes to expand every
+        // cofactor and the determinant separately.
+        let b00 = self.m11 * self.m22 - self.m12 * self.m21;
+        let b01 = self.m11 * self.m23 - self.m13 * self.m21;
+        let b02 = self.m11 * self.m24 - self.m14 * self.m21;
+        let b03 = self.m12 * self.m23 - self.m13 * self.m22;
+        let b04 = self.m12 * self.m24 - self.m14 * self.m22;
+        let b05 = self.m13 * self.m24 - self.m14 * self.m23;
+        let b06 = self.m31 * self.m42 - self.m32 * self.m41;
+        let b07 = self.m31 * self.m43 - self.m33 * self.m41;
+        let b08 = self.m31 * self.m44 - self.m34 * self.m41;
+        let b09 = self.m32 * self.m43 - self.m33 * self.m42;
+        let b10 = self.m32 * self.m44 - self.m34 * self.m42;
+        let b11 = self.m33 * self.m44 - self.m34 * self.m43;
+
+        let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
 
         if det == 0.0 {
             return Matrix4::identity();
         }
 
-        // todo(gw): this could be made faster by special casing
-        // for simpler matrix types.
         let m = Matrix4::new(
-            self.m23*self.m34*self.m42 - self.m24*self.m33*self.m42 +
-            self.m24*self.m32*self.m43 - self.m22*self.m34*self.m43 -
-            self.m23*self.m32*self.m44 + self.m22*self.m33*self.m44,
-
-            self.m14*self.m33*self.m42 - self.m13*self.m34*self.m42 -
-            self.m14*self.m32*self.m43 + self.m12*self.m34*self.m43 +
-            self.m13*self.m32*self.m44 - self.m12*self.m33*self.m44,
-
-            self.m13*self.m24*self.m42 - self.m14*self.m23*self.m42 +
-            self.m14*self.m22*self.m43 - self.m12*self.m24*self.m43 -
-            self.m13*self.m22*self.m44 + self.m12*self.m23*self.m44,
-
-            self.m14*self.m23*self.m32 - self.m13*self.m24*self.m32 -
-            self.m14*self.m22*self.m33 + self.m12*self.m24*self.m33 +
-            self.m13*self.m22*self.m34 - self.m12*self.m23*self.m34,
+            self.m22 * b11 - self.m23 * b10 + self.m24 * b09,
+            self.m13 * b10 - self.m12 * b11 - self.m14 * b09,
+            self.m42 * b05 - self.m43 * b04 + self.m44 * b03,
+            self.m33 * b04 - self.m32 * b05 - self.m34 * b03,
 
-            self.m24*self.m33*self.m41 - self.m23*self.m34*self.m41 -
-            self.m24*self.m31*self.m43 + self.m21*self.m34*self.m43 +
-            self.m23*self.m31*self.m44 - self.m21*self.m33*self.m44,
+            self.m23 * b08 - self.m21 * b11 - self.m24 * b07,
+            self.m11 * b11 - self.m13 * b08 + self.m14 * b07,
+            self.m43 * b02 - self.m41 * b05 - self.m44 * b01,
+            self.m31 * b05 - self.m33 * b02 + self.m34 * b01,
 
-            self.m13*self.m34*self.m41 - self.m14*self.m33*self.m41 +
-            self.m14*self.m31*self.m43 - self.m11*self.m34*self.m43 -
-            self.m13*self.m31*self.m44 + self.m11*self.m33*self.m44,
+            self.m21 * b10 - self.m22 * b08 + self.m24 * b06,
+            self.m12 * b08 - self.m11 * b10 - self.m14 * b06,
+            self.m41 * b04 - self.m42 * b02 + self.m44 * b00,
+            self.m32 * b02 - self.m31 * b04 - self.m34 * b00,
 
-            self.m14*self.m23*self.m41 - self.m13*self.m24*self.m41 -
-            self.m14*self.m21*self.m43 + self.m11*self.m24*self.m43 +
-            self.m13*self.m21*self.m44 - self.m11*self.m23*self.m44,
-
-            self.m13*self.m24*self.m31 - self.m14*self.m23*self.m31 +
-            self.m14*self.m21*self.m33 - self.m11*self.m24*self.m33 -
-            self.m13*self.m21*self.m34 + self.m11*self.m23*self.m34,
-
-            self.m22*self.m34*self.m41 - self.m24*self.m32*self.m41 +
-            self.m24*self.m31*self.m42 - self.m21*self.m34*self.m42 -
-            self.m22*self.m31*self.m44 + self.m21*self.m32*self.m44,
-
-            self.m14*self.m32*self.m41 - self.m12*self.m34*self.m41 -
-            self.m14*self.m31*self.m42 + self.m11*self.m34*self.m42 +
-            self.m12*self.m31*self.m44 - self.m11*self.m32*self.m44,
-
-            self.m12*self.m24*self.m41 - self.m14*self.m22*self.m41 +
-            self.m14*self.m21*self.m42 - self.m11*self.m24*self.m42 -
-            self.m12*self.m21*self.m44 + self.m11*self.m22*self.m44,
-
-            self.m14*self.m22*self.m31 - self.m12*self.m24*self.m31 -
-            self.m14*self.m21*self.m32 + self.m11*self.m24*self.m32 +
-            self.m12*self.m21*self.m34 - self.m11*self.m22*self.m34,
-
-            self.m23*self.m32*self.m41 - self.m22*self.m33*self.m41 -
-            self.m23*self.m31*self.m42 + self.m21*self.m33*self.m42 +
-            self.m22*self.m31*self.m43 - self.m21*self.m32*self.m43,
-
-            self.m12*self.m33*self.m41 - self.m13*self.m32*self.m41 +
-            self.m13*self.m31*self.m42 - self.m11*self.m33*self.m42 -
-            self.m12*self.m31*self.m43 + self.m11*self.m32*self.m43,
-
-            self.m13*self.m22*self.m41 - self.m12*self.m23*self.m41 -
-            self.m13*self.m21*self.m42 + self.m11*self.m23*self.m42 +
-            self.m12*self.m21*self.m43 - self.m11*self.m22*self.m43,
-
-            self.m12*self.m23*self.m31 - self.m13*self.m22*self.m31 +
-            self.m13*self.m21*self.m32 - self.m11*self.m23*self.m32 -
-            self.m12*self.m21*self.m33 + self.m11*self.m22*self.m33
+            self.m22 * b07 - self.m21 * b09 - self.m23 * b06,
+            self.m11 * b09 - self.m12 * b07 + self.m13 * b06,
+            self.m42 * b01 - self.m41 * b03 - self.m43 * b00,
+            self.m31 * b03 - self.m32 * b01 + self.m33 * b00
         );
 
         m.mul_s(1.0 / det)
@@ -205,6 +303,161 @@ impl Matrix4 {
         self.m11 * self.m22 * self.m33 * self.m44
     }
 
+    /// Returns whether this transform preserves (`Right`) or mirrors (`Left`) the orientation
+    /// of the coordinate system, from the sign of the determinant of the upper-left 3x3.
+    /// Degenerate transforms with a zero determinant are reported as `Right`.
+    pub fn handedness(&self) -> Handedness {
+        let det = self.m11 * (self.m22 * self.m33 - self.m23 * self.m32) -
+                  self.m12 * (self.m21 * self.m33 - self.m23 * self.m31) +
+                  self.m13 * (self.m21 * self.m32 - self.m22 * self.m31);
+        if det < 0.0 {
+            Handedness::Left
+        } else {
+            Handedness::Right
+        }
+    }
+
+    /// Decomposes a 2d transform into `(translate, rotation, scale, skew)`, following the 2d
+    /// decomposition algorithm of the CSS Transforms spec.
+    /// https://drafts.csswg.org/css-transforms/#decomposing-a-2d-matrix
+    ///
+    /// `rotation` is the angle of the CSS `rotate()` function and `skew` the angle of
+    /// `skewX()`, both in radians. The matrix is equivalent to scaling, then skewing, then
+    /// rotating and finally translating. A flip shows up as a negative scale on one axis.
+    ///
+    /// Only the 2d entries are considered. Returns `None` if the matrix has perspective or is
+    /// singular in 2d.
+    pub fn decompose_2d(&self) -> Option<(Point2D<f32>, f32, Point2D<f32>, f32)> {
+        if self.m14 != 0.0 || self.m24 != 0.0 || self.m44 != 1.0 {
+            return None;
+        }
+
+        let determinant = self.m11 * self.m22 - self.m12 * self.m21;
+        if determinant == 0.0 {
+            return None;
+        }
+
+        // If the determinant is negative, one axis was flipped. Flip the axis with the minimum
+        // unit vector dot product.
+        let mut scale_x = self.m11.hypot(self.m12);
+        let mut flip_y = false;
+        if determinant < 0.0 {
+            if self.m11 < self.m22 {
+                scale_x = -scale_x;
+            } else {
+                flip_y = true;
+            }
+        }
+
+        // Remove the x scale, then make the second row orthogonal to the first; what is
+        // removed is the skew.
+        let (row0x, row0y) = (self.m11 / scale_x, self.m12 / scale_x);
+        let shear = row0x * self.m21 + row0y * self.m22;
+        let (row1x, row1y) = (self.m21 - shear * row0x, self.m22 - shear * row0y);
+        let mut scale_y = row1x.hypot(row1y);
+        if flip_y {
+            scale_y = -scale_y;
+        }
+
+        Some((Point2D::new(self.m41, self.m42),
+              row0y.atan2(row0x),
+              Point2D::new(scale_x, scale_y),
+              (shear / scale_y).atan()))
+    }
+
+    /// Interpolates between this matrix (`t = 0`) and `other` (`t = 1`) by decomposing both
+    /// into translation, rotation, shear and scale. Rotation is interpolated spherically and
+    /// the rest linearly, so a rotation stays rigid through the interpolation rather than
+    /// shrinking and skewing as it would under elementwise interpolation.
+    ///
+    /// Returns `None` if either matrix has perspective or collapses an axis.
+    pub fn interpolate(&self, other: &Matrix4, t: f32) -> Option<Matrix4> {
+        let a = self.decompose_affine()?;
+        let b = other.decompose_affine()?;
+
+        let translate = a.translate + (b.translate - a.translate) * t;
+        let shear = a.shear + (b.shear - a.shear) * t;
+        let scale = a.scale + (b.scale - a.scale) * t;
+        let q = quaternion_to_rows(&slerp(&a.rotation, &b.rotation, t));
+        let x = q[0] * scale.x;
+        let y = (q[1] + q[0] * shear.x) * scale.y;
+        let z = (q[2] + q[0] * shear.y + q[1] * shear.z) * scale.z;
+        Some(Matrix4::new(x.x, x.y, x.z, 0.0,
+                          y.x, y.y, y.z, 0.0,
+                          z.x, z.y, z.z, 0.0,
+                          translate.x, translate.y, translate.z, 1.0))
+    }
+
+    /// Splits an affine matrix into the parts described on `Decomposed`.
+    ///
+    /// The rows of the upper-left 3x3 are orthogonalized in order, as in the 3d decomposition
+    /// of the CSS Transforms spec, so that recomposing the parts reproduces the matrix.
+    fn decompose_affine(&self) -> Option<Decomposed> {
+        if !self.is_affine() {
+            return None;
+        }
+        let mut rows = [Point3D::new(self.m11, self.m12, self.m13),
+                        Point3D::new(self.m21, self.m22, self.m23),
+                        Point3D::new(self.m31, self.m32, self.m33)];
+
+        let mut scale_x = rows[0].length();
+        if scale_x.approx_eq(&0.0) {
+            return None;
+        }
+        rows[0] = rows[0] / scale_x;
+
+        let mut shear_xy = rows[0].dot(rows[1]);
+        rows[1] = rows[1] - rows[0] * shear_xy;
+        let mut scale_y = rows[1].length();
+        if scale_y.approx_eq(&0.0) {
+            return None;
+        }
+        rows[1] = rows[1] / scale_y;
+        shear_xy /= scale_y;
+
+        let mut shear_xz = rows[0].dot(rows[2]);
+        rows[2] = rows[2] - rows[0] * shear_xz;
+        let mut shear_yz = rows[1].dot(rows[2]);
+        rows[2] = rows[2] - rows[1] * shear_yz;
+        let mut scale_z = rows[2].length();
+        if scale_z.approx_eq(&0.0) {
+            return None;
+        }
+        rows[2] = rows[2] / scale_z;
+        shear_xz /= scale_z;
+        shear_yz /= scale_z;
+
+        // A mirror leaves the rows a left-handed basis, which no quaternion represents. Flip
+        // them, and the scale with them, so that they form a rotation.
+        if rows[0].dot(rows[1].cross(rows[2])) < 0.0 {
+            scale_x = -scale_x;
+            scale_y = -scale_y;
+            scale_z = -scale_z;
+            rows = [-rows[0], -rows[1], -rows[2]];
+        }
+
+        Some(Decomposed {
+            translate: Point3D::new(self.m41, self.m42, self.m43),
+            rotation: rows_to_quaternion(&rows),
+            shear: Point3D::new(shear_xy, shear_xz, shear_yz),
+            scale: Point3D::new(scale_x, scale_y, scale_z),
+        })
+    }
+
+    /// Like `mul_s`, but returns `None` if `x` or any entry of the result is infinite or NaN,
+    /// e.g. when scaling by the reciprocal of a tiny determinant.
+    pub fn checked_mul_s(&self, x: f32) -> Option<Matrix4> {
+        if !x.is_finite() {
+            return None;
+        }
+        let m = self.mul_s(x);
+        if m.to_array().iter().all(|e| e.is_finite()) {
+            Some(m)
+        } else {
+            None
+        }
+    }
+
     pub fn mul_s(&self, x: f32) -> Matrix4 {
         Matrix4::new(self.m11 * x, self.m12 * x, self.m13 * x, self.m14 * x,
                      self.m21 * x, self.m22 * x, self.m23 * x, self.m24 * x,
@@ -212,11 +465,13 @@ impl Matrix4 {
                      self.m41 * x, self.m42 * x, self.m43 * x, self.m44 * x)
     }
 
+    /// Returns this matrix composed with a scale, in the same order as `translate`: the scale
+    /// is applied to points before `self`.
+    ///
+    /// This used to multiply only the diagonal entries, which agrees with the composition
+    /// for pure scale/translation matrices but not once `self` has rotation or skew.
     pub fn scale(&self, x: f32, y: f32, z: f32) -> Matrix4 {
-        Matrix4::new(self.m11 * x, self.m12,     self.m13,     self.m14,
-                     self.m21    , self.m22 * y, self.m23,     self.m24,
-                     self.m31    , self.m32,     self.m33 * z, self.m34,
-                     self.m41    , self.m42,     self.m43,     self.m44)
+        self.mul(&Matrix4::create_scale(x, y, z))
     }
 
     /// Returns the given point transformed by this matrix.
@@ -226,6 +481,27 @@ impl Matrix4 {
                      p.x * self.m12 + p.y * self.m22 + self.m42)
     }
 
+    /// Returns the direction `v` transformed by the upper-left 3x3 of this matrix, ignoring
+    /// translation and perspective. Suits tangents and other differences of points; normals
+    /// need the inverse transpose instead.
+    #[inline]
+    pub fn transform_direction3d(&self, v: &Point3D<f32>) -> Point3D<f32> {
+        Point3D::new(v.x * self.m11 + v.y * self.m21 + v.z * self.m31,
+                     v.x * self.m12 + v.y * self.m22 + v.z * self.m32,
+                     v.x * self.m13 + v.y * self.m23 + v.z * self.m33)
+    }
+
+    /// Transforms the ray from `origin` along `dir`, returning the new origin and direction.
+    /// The origin gets the full transform, including the divide by w under perspective, and
+    /// the direction only the linear part as in `transform_direction3d`. The direction is not
+    /// renormalized.
+    pub fn transform_ray(&self, origin: &Point3D<f32>, dir: &Point3D<f32>)
+                         -> (Point3D<f32>, Point3D<f32>) {
+        let origin = Point4D::new(origin.x, origin.y, origin.z, 1.0);
+        (self.transform_point4d(&origin).normalize_homogeneous().xyz(),
+         self.transform_direction3d(dir))
+    }
+
     #[inline]
     pub fn transform_point4d(&self, p: &Point4D<f32>) -> Point4D<f32> {
         let x = p.x * self.m11 + p.y * self.m21 + p.z * self.m31 + self.m41;
@@ -235,6 +511,37 @@ impl Matrix4 {
         Point4D::new(x, y, z, w)
     }
 
+    /// Applies the perspective divide to a buffer of transformed points in place, dividing
+    /// x, y and z by w and leaving w as 1.
+    ///
+    /// Points with `w == 0` are at infinity and are left unchanged, as with
+    /// `Point4D::normalize_homogeneous`; callers that need to can find them by their w.
+    pub fn perspective_divide(points: &mut [Point4D<f32>]) {
+        for p in points.iter_mut() {
+            *p = p.normalize_homogeneous();
+        }
+    }
+
+    /// Returns the bounding rectangle of `rect` transformed by this matrix, including the
+    /// perspective divide of each corner.
+    ///
+    /// Returns `None` if any corner ends up with `w <= 0`, i.e. at or behind the viewer, where
+    /// the projected corners no longer bound the projected rectangle.
+    pub fn transform_rect_perspective(&self, rect: &Rect<f32>) -> Option<Rect<f32>> {
+        let mut min = Point2D::new(::std::f32::INFINITY, ::std::f32::INFINITY);
+        let mut max = Point2D::new(::std::f32::NEG_INFINITY, ::std::f32::NEG_INFINITY);
+        for corner in rect.corners().iter() {
+            let p = self.transform_point4d(&Point4D::new(corner.x, corner.y, 0.0, 1.0));
+            if p.w <= 0.0 {
+                return None;
+            }
+            let projected = Point2D::new(p.x / p.w, p.y / p.w);
+            min = min.min(projected);
+            max = max.max(projected);
+        }
+        Some(Rect::new(min, Size2D::new(max.x - min.x, max.y - min.y)))
+    }
+
     pub fn to_array(&self) -> [f32; 16] {
         [
             self.m11, self.m12, self.m13, self.m14,
@@ -244,6 +551,27 @@ impl Matrix4 {
         ]
     }
 
+    /// Returns the entries as an array of rows, the inverse of `From<[[f32; 4]; 4]>`.
+    pub fn to_2d_array_nested(&self) -> [[f32; 4]; 4] {
+        [
+            [self.m11, self.m12, self.m13, self.m14],
+            [self.m21, self.m22, self.m23, self.m24],
+            [self.m31, self.m32, self.m33, self.m34],
+            [self.m41, self.m42, self.m43, self.m44]
+        ]
+    }
+
+    /// Formats the matrix as four lines, one per row, with the columns aligned.
+    pub fn pretty(&self) -> String {
+        let entries: Vec<String> = self.to_array().iter().map(|e| e.to_string()).collect();
+        let width = entries.iter().map(|e| e.len()).max().unwrap_or(0);
+        let rows: Vec<String> = entries.chunks(4).map(|row| {
+            let cells: Vec<String> = row.iter().map(|e| format!("{:>1$}", e, width)).collect();
+            cells.join(" ")
+        }).collect();
+        rows.join("\n")
+    }
+
     pub fn translate(&self, x: f32, y: f32, z: f32) -> Matrix4 {
         let matrix = Matrix4::new(1.0, 0.0, 0.0, 0.0,
                                   0.0, 1.0, 0.0, 0.0,
@@ -269,6 +597,26 @@ impl Matrix4 {
                      0.0, 0.0, 0.0, 1.0)
     }
 
+    /// Create a 3d scale matrix that scales about `pivot` rather than the origin.
+    pub fn create_scale_around(pivot: &Point3D<f32>, x: f32, y: f32, z: f32) -> Matrix4 {
+        Matrix4::new(                 x,                  0.0,                  0.0, 0.0,
+                                    0.0,                    y,                  0.0, 0.0,
+                                    0.0,                  0.0,                    z, 0.0,
+                     pivot.x * (1.0 - x), pivot.y * (1.0 - y), pivot.z * (1.0 - z), 1.0)
+    }
+
+    /// Create a 2d translation matrix, leaving z unchanged.
+    #[inline]
+    pub fn create_translation_2d(x: f32, y: f32) -> Matrix4 {
+        Matrix4::create_translation(x, y, 0.0)
+    }
+
+    /// Create a 2d scale matrix, leaving z unchanged.
+    #[inline]
+    pub fn create_scale_2d(x: f32, y: f32) -> Matrix4 {
+        Matrix4::create_scale(x, y, 1.0)
+    }
+
     /// Create a 3d rotation matrix from an angle / axis.
     /// The supplied axis must be normalized.
     pub fn create_rotation(x: f32, y: f32, z: f32, theta: f32) -> Matrix4 {
@@ -303,16 +651,85 @@ impl Matrix4 {
         )
     }
 
+    /// Like `create_rotation`, but normalizes the axis first so it can have any length. An
+    /// axis too close to zero to have a direction gives the identity.
+    pub fn create_rotation_normalized(x: f32, y: f32, z: f32, theta: f32) -> Matrix4 {
+        let length = Point3D::new(x, y, z).length();
+        if length.approx_eq(&0.0) {
+            return Matrix4::identity();
+        }
+        Matrix4::create_rotation(x / length, y / length, z / length, theta)
+    }
+
+    /// Create a matrix whose first three rows are `right`, `up` and `forward`, so that it maps
+    /// the x, y and z axes onto them. For an orthonormal basis this is a rotation, and its
+    /// transpose (its inverse) maps world directions back into the frame.
+    pub fn create_basis(right: &Point3D<f32>, up: &Point3D<f32>, forward: &Point3D<f32>)
+                        -> Matrix4 {
+        Matrix4::new(right.x, right.y, right.z, 0.0,
+                     up.x, up.y, up.z, 0.0,
+                     forward.x, forward.y, forward.z, 0.0,
+                     0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Create a 3d rotation matrix about `axis` through `pivot`, rather than through the
+    /// origin. As with `create_rotation`, the axis must be normalized.
+    pub fn create_rotation_around(pivot: &Point3D<f32>, axis: &Point3D<f32>, theta: f32)
+                                  -> Matrix4 {
+        Matrix4::create_translation(pivot.x, pivot.y, pivot.z)
+            .mul(&Matrix4::create_rotation(axis.x, axis.y, axis.z, theta))
+            .mul(&Matrix4::create_translation(-pivot.x, -pivot.y, -pivot.z))
+    }
+
+    /// Create a right-handed view matrix for a camera at `eye` looking at `target`, with `up`
+    /// giving the upward direction, as `gluLookAt` does.
+    pub fn create_look_at(eye: &Point3D<f32>, target: &Point3D<f32>, up: &Point3D<f32>)
+                          -> Matrix4 {
+        Matrix4::create_look_to(eye, &(*target - *eye), up)
+    }
+
+    /// Create a right-handed view matrix for a camera at `eye` looking along `dir`, with `up`
+    /// giving the upward direction. Neither `dir` nor `up` needs to be normalized.
+    pub fn create_look_to(eye: &Point3D<f32>, dir: &Point3D<f32>, up: &Point3D<f32>)
+                          -> Matrix4 {
+        let f = *dir / dir.length();
+        let s = f.cross(*up);
+        let s = s / s.length();
+        let u = s.cross(f);
+
+        Matrix4::new(s.x, u.x, -f.x, 0.0,
+                     s.y, u.y, -f.y, 0.0,
+                     s.z, u.z, -f.z, 0.0,
+                     -s.dot(*eye), -u.dot(*eye), f.dot(*eye), 1.0)
+    }
+
     /// Create a 2d skew matrix.
     /// https://drafts.csswg.org/css-transforms/#funcdef-skew
+    ///
+    /// `alpha` skews along the x axis and `beta` along the y axis, as in CSS `skew(alpha,
+    /// beta)`. Because points are row vectors here, `tan(alpha)` ends up in `m21`.
     pub fn create_skew(alpha: f32, beta: f32) -> Matrix4 {
-        let (sx, sy) = (beta.tan(), alpha.tan());
-        Matrix4::new(1.0,  sx, 0.0, 0.0,
-                      sy, 1.0, 0.0, 0.0,
+        // CSS skew(alpha, beta) is matrix(1, tan(beta), tan(alpha), 1, 0, 0), whose entries
+        // are laid out in the same order as m11, m12, m21, m22.
+        let (tan_alpha, tan_beta) = (alpha.tan(), beta.tan());
+        Matrix4::new(      1.0, tan_beta, 0.0, 0.0,
+                     tan_alpha,      1.0, 0.0, 0.0,
                      0.0, 0.0, 1.0, 0.0,
                      0.0, 0.0, 0.0, 1.0)
     }
 
+    /// Create a 2d skew matrix along the x axis, like CSS `skewX(angle)`.
+    #[inline]
+    pub fn create_skew_x(angle: f32) -> Matrix4 {
+        Matrix4::create_skew(angle, 0.0)
+    }
+
+    /// Create a 2d skew matrix along the y axis, like CSS `skewY(angle)`.
+    #[inline]
+    pub fn create_skew_y(angle: f32) -> Matrix4 {
+        Matrix4::create_skew(0.0, angle)
+    }
+
     /// Create a simple perspective projection matrix
     pub fn create_perspective(d: f32) -> Matrix4 {
         Matrix4::new(1.0, 0.0, 0.0, 0.0,
@@ -320,6 +737,148 @@ impl Matrix4 {
                      0.0, 0.0, 1.0, -1.0 / d,
                      0.0, 0.0, 0.0, 1.0)
     }
+
+    /// Create a perspective projection with the far plane at infinity, for a vertical field
+    /// of view of `fov_y` radians and an `aspect` ratio of width over height.
+    ///
+    /// This follows the OpenGL conventions of `gluPerspective`: the camera looks down -z, and
+    /// depth maps to `[-1, 1]` in normalized device coordinates, with `z = -near` at -1 and
+    /// points approaching 1 as they recede to infinity.
+    pub fn create_infinite_perspective(fov_y: f32, aspect: f32, near: f32) -> Matrix4 {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        Matrix4::new(f / aspect, 0.0, 0.0, 0.0,
+                     0.0, f, 0.0, 0.0,
+                     0.0, 0.0, -1.0, -1.0,
+                     0.0, 0.0, -2.0 * near, 0.0)
+    }
+}
+
+/// Elementwise addition, e.g. for accumulating numerical results. This is not transform
+/// composition, which is `mul`.
+impl Add for Matrix4 {
+    type Output = Matrix4;
+    #[inline]
+    fn add(self, other: Matrix4) -> Matrix4 {
+        Matrix4::new(self.m11 + other.m11, self.m12 + other.m12,
+                     self.m13 + other.m13, self.m14 + other.m14,
+                     self.m21 + other.m21, self.m22 + other.m22,
+                     self.m23 + other.m23, self.m24 + other.m24,
+                     self.m31 + other.m31, self.m32 + other.m32,
+                     self.m33 + other.m33, self.m34 + other.m34,
+                     self.m41 + other.m41, self.m42 + other.m42,
+                     self.m43 + other.m43, self.m44 + other.m44)
+    }
+}
+
+/// Elementwise subtraction, the counterpart of `Add`. Subtracting does not undo a transform;
+/// use `invert` for that.
+impl Sub for Matrix4 {
+    type Output = Matrix4;
+    #[inline]
+    fn sub(self, other: Matrix4) -> Matrix4 {
+        Matrix4::new(self.m11 - other.m11, self.m12 - other.m12,
+                     self.m13 - other.m13, self.m14 - other.m14,
+                     self.m21 - other.m21, self.m22 - other.m22,
+                     self.m23 - other.m23, self.m24 - other.m24,
+                     self.m31 - other.m31, self.m32 - other.m32,
+                     self.m33 - other.m33, self.m34 - other.m34,
+                     self.m41 - other.m41, self.m42 - other.m42,
+                     self.m43 - other.m43, self.m44 - other.m44)
+    }
+}
+
+/// Builds a matrix from an array of rows, so `rows[i][j]` becomes `m(i+1)(j+1)`.
+impl From<[[f32; 4]; 4]> for Matrix4 {
+    fn from(rows: [[f32; 4]; 4]) -> Matrix4 {
+        let [r1, r2, r3, r4] = rows;
+        Matrix4::new(r1[0], r1[1], r1[2], r1[3],
+                     r2[0], r2[1], r2[2], r2[3],
+                     r3[0], r3[1], r3[2], r3[3],
+                     r4[0], r4[1], r4[2], r4[3])
+    }
+}
+
+impl MulAssign for Matrix4 {
+    #[inline]
+    fn mul_assign(&mut self, other: Matrix4) {
+        Matrix4::mul_assign(self, &other)
+    }
+}
+
+/// `p * m` multiplies `p` as a row vector on the left of `m`, the convention this crate
+/// stores matrices in. It agrees with `m.transform_point4d(&p)` when `p.w` is 1; unlike
+/// `transform_point4d`, which assumes w is 1, it also scales the last row by `p.w`.
+impl Mul<Matrix4> for Point4D<f32> {
+    type Output = Point4D<f32>;
+    #[inline]
+    fn mul(self, m: Matrix4) -> Point4D<f32> {
+        Point4D::new(self.x * m.m11 + self.y * m.m21 + self.z * m.m31 + self.w * m.m41,
+                     self.x * m.m12 + self.y * m.m22 + self.z * m.m32 + self.w * m.m42,
+                     self.x * m.m13 + self.y * m.m23 + self.z * m.m33 + self.w * m.m43,
+                     self.x * m.m14 + self.y * m.m24 + self.z * m.m34 + self.w * m.m44)
+    }
+}
+
+/// The parts of an affine matrix, as split by `Matrix4::decompose_affine`.
+struct Decomposed {
+    translate: Point3D<f32>,
+    /// A unit quaternion `[x, y, z, w]`.
+    rotation: [f32; 4],
+    /// The xy, xz and yz shear factors.
+    shear: Point3D<f32>,
+    /// Negative on every axis for a mirror.
+    scale: Point3D<f32>,
+}
+
+/// Converts the rows of a rotation matrix to a unit quaternion `[x, y, z, w]`, choosing the
+/// largest of the four candidate divisors for accuracy.
+fn rows_to_quaternion(r: &[Point3D<f32>; 3]) -> [f32; 4] {
+    let trace = r[0].x + r[1].y + r[2].z;
+    if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [(r[1].z - r[2].y) / s, (r[2].x - r[0].z) / s, (r[0].y - r[1].x) / s, s / 4.0]
+    } else if r[0].x > r[1].y && r[0].x > r[2].z {
+        let s = (1.0 + r[0].x - r[1].y - r[2].z).sqrt() * 2.0;
+        [s / 4.0, (r[1].x + r[0].y) / s, (r[2].x + r[0].z) / s, (r[1].z - r[2].y) / s]
+    } else if r[1].y > r[2].z {
+        let s = (1.0 + r[1].y - r[0].x - r[2].z).sqrt() * 2.0;
+        [(r[1].x + r[0].y) / s, s / 4.0, (r[2].y + r[1].z) / s, (r[2].x - r[0].z) / s]
+    } else {
+        let s = (1.0 + r[2].z - r[0].x - r[1].y).sqrt() * 2.0;
+        [(r[2].x + r[0].z) / s, (r[2].y + r[1].z) / s, s / 4.0, (r[0].y - r[1].x) / s]
+    }
+}
+
+/// The inverse of `rows_to_quaternion`.
+fn quaternion_to_rows(q: &[f32; 4]) -> [Point3D<f32>; 3] {
+    let [x, y, z, w] = *q;
+    [Point3D::new(1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w)),
+     Point3D::new(2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w)),
+     Point3D::new(2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y))]
+}
+
+/// Spherically interpolates between two unit quaternions along the shorter arc.
+fn slerp(a: &[f32; 4], b: &[f32; 4], t: f32) -> [f32; 4] {
+    let mut dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    let mut b = *b;
+    if dot < 0.0 {
+        // q and -q are the same rotation; flip one to take the short way round.
+        dot = -dot;
+        b = [-b[0], -b[1], -b[2], -b[3]];
+    }
+
+    let (wa, wb) = if dot > 0.9995 {
+        // Nearly parallel: the sines below vanish, and a linear blend is just as good.
+        (1.0 - t, t)
+    } else {
+        let theta = dot.acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    let q = [wa * a[0] + wb * b[0], wa * a[1] + wb * b[1],
+             wa * a[2] + wb * b[2], wa * a[3] + wb * b[3]];
+    let length = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    [q[0] / length, q[1] / length, q[2] / length, q[3] / length]
 }
 
 #[test]
@@ -335,6 +894,50 @@ pub fn test_ortho() {
     assert!(result.approx_eq(&expected));
 }
 
+#[test]
+pub fn test_create_screen_ortho() {
+    let m = Matrix4::create_screen_ortho(800.0, 600.0);
+    let maps_to = |x: f32, y: f32, ndc_x: f32, ndc_y: f32| {
+        let ndc = m.transform_point(&Point2D::new(x, y));
+        (ndc - Point2D::new(ndc_x, ndc_y)).approx_is_zero(1e-6)
+    };
+    assert!(maps_to(0.0, 0.0, -1.0, 1.0));
+    assert!(maps_to(800.0, 600.0, 1.0, -1.0));
+    assert!(maps_to(800.0, 0.0, 1.0, 1.0));
+    assert!(maps_to(400.0, 300.0, 0.0, 0.0));
+}
+
+#[test]
+pub fn test_create_viewport() {
+    let m = Matrix4::create_viewport(10.0, 20.0, 800.0, 600.0, 0.0, 1.0);
+    assert!(m.transform_point4d(&Point4D::new(-1.0, -1.0, -1.0, 1.0)) ==
+            Point4D::new(10.0, 20.0, 0.0, 1.0));
+    assert!(m.transform_point4d(&Point4D::new(1.0, 1.0, 1.0, 1.0)) ==
+            Point4D::new(810.0, 620.0, 1.0, 1.0));
+    assert!(m.transform_point4d(&Point4D::new(1.0, -1.0, 0.0, 1.0)) ==
+            Point4D::new(810.0, 20.0, 0.5, 1.0));
+
+    // In 2d, undoes create_screen_ortho when given the flipped height.
+    let screen = Matrix4::create_viewport(0.0, 600.0, 800.0, -600.0, -1.0, 1.0)
+        .mul(&Matrix4::create_screen_ortho(800.0, 600.0));
+    assert!(screen.approx_eq_2d(&Matrix4::identity()));
+}
+
+#[test]
+pub fn test_create_infinite_perspective() {
+    let m = Matrix4::create_infinite_perspective(::std::f32::consts::FRAC_PI_2, 2.0, 0.1);
+    let ndc = |x: f32, y: f32, z: f32| {
+        m.transform_point4d(&Point4D::new(x, y, z, 1.0)).normalize_homogeneous()
+    };
+
+    let near = ndc(0.2, 0.1, -0.1);
+    assert!(near.x.approx_eq(&1.0) && near.y.approx_eq(&1.0) && near.z.approx_eq(&-1.0));
+
+    let far = ndc(0.0, 0.0, -1.0e6);
+    assert!(far.z < 1.0 && far.z.approx_eq(&1.0));
+    assert!(ndc(0.0, 0.0, -10.0).z < far.z);
+}
+
 #[test]
 pub fn test_invert_simple() {
     let m1 = Matrix4::identity();
@@ -376,3 +979,476 @@ pub fn test_invert_transform_point_2d() {
     let p3 = m2.transform_point(&p2);
     assert!(p3.eq(&p1));
 }
+
+#[test]
+pub fn test_mul_assign() {
+    let steps = [Matrix4::create_translation(10.0, -3.0, 2.0),
+                 Matrix4::create_rotation(0.0, 0.0, 1.0, 0.7),
+                 Matrix4::create_scale(2.0, 0.5, 1.5)];
+
+    let mut by_value = Matrix4::identity();
+    let mut in_place = Matrix4::identity();
+    let mut by_operator = Matrix4::identity();
+    for step in steps.iter() {
+        by_value = by_value.mul(step);
+        in_place.mul_assign(step);
+        by_operator *= *step;
+    }
+
+    assert!(in_place.approx_eq(&by_value));
+    assert!(by_operator.approx_eq(&by_value));
+}
+
+#[test]
+pub fn test_mul_2d() {
+    let m1 = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.5).translate(0.0, 0.0, 7.0);
+    let m2 = Matrix2D::new(2.0, 0.5, -1.0, 3.0, 10.0, 20.0);
+
+    assert!(m1.pre_mul_2d(&m2).approx_eq(&m2.to_matrix4().mul(&m1)));
+    assert!(m1.post_mul_2d(&m2).approx_eq(&m1.mul(&m2.to_matrix4())));
+
+    // The 2d transform is applied to points before `m1` when post-multiplied.
+    let p = Point2D::new(1.0, 2.0);
+    let expected = m1.transform_point(&m2.transform_point(&p));
+    let actual = m1.post_mul_2d(&m2).transform_point(&p);
+    assert!(actual.x.approx_eq(&expected.x) && actual.y.approx_eq(&expected.y));
+}
+
+#[test]
+pub fn test_create_2d() {
+    assert!(Matrix4::create_translation_2d(3.0, -4.0) ==
+            Matrix4::create_translation(3.0, -4.0, 0.0));
+    assert!(Matrix4::create_scale_2d(2.0, 0.5) == Matrix4::create_scale(2.0, 0.5, 1.0));
+}
+
+#[test]
+pub fn test_scale_rotated() {
+    let rotation = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.5);
+    let scaled = rotation.scale(2.0, 3.0, 1.0);
+    assert!(scaled.approx_eq(&rotation.mul(&Matrix4::create_scale(2.0, 3.0, 1.0))));
+
+    // Scaling only the diagonal entries, as `scale` used to, does not scale the rotated basis.
+    let mut diagonal_only = rotation;
+    diagonal_only.m11 *= 2.0;
+    diagonal_only.m22 *= 3.0;
+    assert!(!scaled.approx_eq(&diagonal_only));
+
+    // The scale stretches the input before it is rotated.
+    let p = scaled.transform_point(&Point2D::new(1.0, 0.0));
+    let expected = rotation.transform_point(&Point2D::new(2.0, 0.0));
+    assert!(p.x.approx_eq(&expected.x) && p.y.approx_eq(&expected.y));
+}
+
+#[test]
+pub fn test_decompose_2d() {
+    // Builds scale, then skewX, then rotate, then translate, as CSS would.
+    fn compose(translate: Point2D<f32>, angle: f32, scale: Point2D<f32>, skew: f32) -> Matrix4 {
+        let (s, c, k) = (angle.sin(), angle.cos(), skew.tan());
+        Matrix4::new(scale.x * c,                 scale.x * s,                 0.0, 0.0,
+                     scale.y * (k * c - s),       scale.y * (k * s + c),       0.0, 0.0,
+                     0.0,                         0.0,                         1.0, 0.0,
+                     translate.x,                 translate.y,                 0.0, 1.0)
+    }
+
+    fn check(translate: Point2D<f32>, angle: f32, scale: Point2D<f32>, skew: f32) {
+        let m = compose(translate, angle, scale, skew);
+        let (t, r, s, k) = m.decompose_2d().unwrap();
+        assert!(t.x.approx_eq(&translate.x) && t.y.approx_eq(&translate.y));
+        assert!(r.approx_eq_eps(&angle, &1.0e-5));
+        assert!(s.x.approx_eq_eps(&scale.x, &1.0e-5) && s.y.approx_eq_eps(&scale.y, &1.0e-5));
+        assert!(k.approx_eq_eps(&skew, &1.0e-5));
+        assert!(compose(t, r, s, k).approx_eq(&m));
+    }
+
+    check(Point2D::new(10.0, 20.0), 0.5, Point2D::new(2.0, 3.0), 0.0);
+    check(Point2D::new(-4.0, 0.0), -1.2, Point2D::new(0.5, 1.5), 0.3);
+    check(Point2D::new(0.0, 0.0), 0.5, Point2D::new(-2.0, 3.0), 0.0);
+
+    assert!(Matrix4::create_scale(1.0, 0.0, 1.0).decompose_2d().is_none());
+    assert!(Matrix4::create_perspective(100.0).mul(&Matrix4::create_rotation(1.0, 0.0, 0.0, 0.5))
+                                                 .decompose_2d().is_none());
+}
+
+#[test]
+pub fn test_create_scale_around() {
+    let pivot = Point3D::new(10.0, -20.0, 5.0);
+    let m = Matrix4::create_scale_around(&pivot, 2.0, 3.0, 0.5);
+
+    let expected = Matrix4::create_translation(pivot.x, pivot.y, pivot.z)
+                       .mul(&Matrix4::create_scale(2.0, 3.0, 0.5))
+                       .mul(&Matrix4::create_translation(-pivot.x, -pivot.y, -pivot.z));
+    assert!(m.approx_eq(&expected));
+
+    let p = m.transform_point4d(&Point4D::new(pivot.x, pivot.y, pivot.z, 1.0));
+    assert!(p == Point4D::new(pivot.x, pivot.y, pivot.z, 1.0));
+
+    let q = m.transform_point4d(&Point4D::new(11.0, -19.0, 7.0, 1.0));
+    assert!(q == Point4D::new(12.0, -17.0, 6.0, 1.0));
+}
+
+#[test]
+pub fn test_create_rotation_around() {
+    use std::f32::consts;
+
+    let pivot = Point3D::new(10.0, 10.0, 0.0);
+    let m = Matrix4::create_rotation_around(&pivot, &Point3D::new(0.0, 0.0, 1.0),
+                                            consts::FRAC_PI_2);
+
+    let p = m.transform_point(&Point2D::new(10.0, 10.0));
+    assert!(p.x.approx_eq(&10.0) && p.y.approx_eq(&10.0));
+
+    let q = m.transform_point(&Point2D::new(11.0, 10.0));
+    let expected = Matrix4::create_rotation(0.0, 0.0, 1.0, consts::FRAC_PI_2)
+                       .transform_point(&Point2D::new(1.0, 0.0)) + Point2D::new(10.0, 10.0);
+    assert!(q.x.approx_eq(&expected.x) && q.y.approx_eq(&expected.y));
+    assert!(q.x.approx_eq(&10.0) && q.y.approx_eq(&9.0));
+}
+
+#[test]
+pub fn test_pretty() {
+    let m = Matrix4::create_scale(2.5, -3.0, 100.0).translate(1.0, 2.0, 3.0);
+    let pretty = m.pretty();
+    let lines: Vec<&str> = pretty.lines().collect();
+
+    assert!(lines.len() == 4);
+    assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    assert!(lines[0].contains("2.5"));
+    assert!(lines[1].contains("-3"));
+    assert!(lines[2].contains("100"));
+    assert!(lines[3].ends_with(" 1"));
+}
+
+#[bench]
+pub fn bench_mul(b: &mut ::test::Bencher) {
+    let m1 = Matrix4::create_rotation(0.0, 1.0, 0.0, 0.3);
+    let m2 = Matrix4::create_translation(1.0, 2.0, 3.0);
+    b.iter(|| ::test::black_box(&m1).mul(::test::black_box(&m2)));
+}
+
+#[test]
+pub fn test_is_affine() {
+    assert!(Matrix4::identity().is_affine());
+    assert!(Matrix4::create_rotation(0.0, 1.0, 0.0, 0.3).translate(1.0, 2.0, 3.0).is_affine());
+    assert!(!Matrix4::create_perspective(100.0).is_affine());
+}
+
+#[test]
+pub fn test_is_2d() {
+    let m = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.3).translate(1.0, 2.0, 0.0)
+                                                        .mul(&Matrix4::create_skew(0.1, 0.2));
+    assert!(m.is_2d());
+    assert!(!Matrix4::create_translation(0.0, 0.0, 5.0).is_2d());
+    assert!(!Matrix4::create_rotation(1.0, 0.0, 0.0, 0.3).is_2d());
+}
+
+#[test]
+pub fn test_create_look_to() {
+    let eye = Point3D::new(1.0, 2.0, 3.0);
+    let dir = Point3D::new(-0.5, 0.25, -2.0);
+    let up = Point3D::new(0.0, 1.0, 0.0);
+
+    let look_to = Matrix4::create_look_to(&eye, &dir, &up);
+    assert!(look_to.approx_eq(&Matrix4::create_look_at(&eye, &(eye + dir), &up)));
+
+    let m = Matrix4::create_look_at(&Point3D::new(0.0, 0.0, 5.0), &Point3D::zero(), &up);
+    assert!(m.approx_eq(&Matrix4::create_translation(0.0, 0.0, -5.0)));
+}
+
+#[test]
+pub fn test_create_skew_x_y() {
+    use std::f32::consts;
+
+    assert!(Matrix4::create_skew_x(0.3) == Matrix4::create_skew(0.3, 0.0));
+    assert!(Matrix4::create_skew_y(0.3) == Matrix4::create_skew(0.0, 0.3));
+
+    // skewX shifts x in proportion to y, and skewY shifts y in proportion to x.
+    let p = Matrix4::create_skew_x(consts::FRAC_PI_4).transform_point(&Point2D::new(0.0, 2.0));
+    assert!(p.x.approx_eq(&2.0) && p.y.approx_eq(&2.0));
+    let p = Matrix4::create_skew_y(consts::FRAC_PI_4).transform_point(&Point2D::new(2.0, 0.0));
+    assert!(p.x.approx_eq(&2.0) && p.y.approx_eq(&2.0));
+}
+
+#[test]
+pub fn test_create_skew() {
+    // skew(30deg, 45deg) from the spec is matrix(1, tan(45deg), tan(30deg), 1, 0, 0).
+    let (alpha, beta) = (30.0f32.to_radians(), 45.0f32.to_radians());
+    let expected = Matrix4::new(1.0,          beta.tan(), 0.0, 0.0,
+                                alpha.tan(),  1.0,        0.0, 0.0,
+                                0.0,          0.0,        1.0, 0.0,
+                                0.0,          0.0,        0.0, 1.0);
+    assert!(Matrix4::create_skew(alpha, beta).approx_eq(&expected));
+
+    // The first argument skews along x: x' = x + tan(alpha) * y.
+    let p = Matrix4::create_skew(alpha, beta).transform_point(&Point2D::new(0.0, 1.0));
+    assert!(p.x.approx_eq(&alpha.tan()) && p.y.approx_eq(&1.0));
+}
+
+#[test]
+pub fn test_max_abs_diff() {
+    let m = Matrix4::create_rotation(0.0, 1.0, 0.0, 0.3).translate(1.0, 2.0, 3.0);
+    assert!(m.max_abs_diff(&m) == 0.0);
+
+    let mut offset = m;
+    offset.m23 += 0.25;
+    offset.m41 -= 0.5;
+    assert!(m.max_abs_diff(&offset).approx_eq(&0.5));
+    assert!(offset.max_abs_diff(&m).approx_eq(&0.5));
+}
+
+#[test]
+pub fn test_transform_rect_perspective() {
+    let rect = Rect::new(Point2D::new(-50.0, 20.0), Size2D::new(100.0, 50.0));
+    let perspective = Matrix4::create_perspective(100.0);
+
+    // Pushed back by the perspective distance, everything appears at half size.
+    let m = perspective.mul(&Matrix4::create_translation(0.0, 0.0, -100.0));
+    let projected = m.transform_rect_perspective(&rect).unwrap();
+    assert!(projected == Rect::new(Point2D::new(-25.0, 10.0), Size2D::new(50.0, 25.0)));
+
+    // Tilted away from the viewer, the nearest edge (y = 20) is the widest.
+    let m = perspective.mul(&Matrix4::create_rotation(1.0, 0.0, 0.0, 0.5));
+    let projected = m.transform_rect_perspective(&rect).unwrap();
+    let near_w = 1.0 + 20.0 * 0.5f32.sin() / 100.0;
+    assert!(projected.origin.x.approx_eq_eps(&(-50.0 / near_w), &1.0e-4));
+    assert!(projected.size.width.approx_eq_eps(&(100.0 / near_w), &1.0e-4));
+
+    // Behind the viewer there is no meaningful projection.
+    let m = perspective.mul(&Matrix4::create_translation(0.0, 0.0, 200.0));
+    assert!(m.transform_rect_perspective(&rect).is_none());
+}
+
+#[test]
+pub fn test_perspective_divide() {
+    let mut points = [Point4D::new(2.0, 4.0, 6.0, 2.0),
+                      Point4D::new(1.0, -1.0, 0.5, 0.5),
+                      Point4D::new(3.0, 3.0, 3.0, 0.0)];
+    Matrix4::perspective_divide(&mut points);
+
+    assert!(points[0] == Point4D::new(1.0, 2.0, 3.0, 1.0));
+    assert!(points[1] == Point4D::new(2.0, -2.0, 1.0, 1.0));
+    assert!(points[2] == Point4D::new(3.0, 3.0, 3.0, 0.0));
+}
+
+#[test]
+pub fn test_handedness() {
+    assert!(Matrix4::identity().handedness() == Handedness::Right);
+    assert!(Matrix4::create_rotation(0.0, 0.0, 1.0, 1.0).handedness() == Handedness::Right);
+    assert!(Matrix4::create_scale(-1.0, 1.0, 1.0).handedness() == Handedness::Left);
+    // Two mirrors cancel out.
+    assert!(Matrix4::create_scale(-1.0, -1.0, 1.0).handedness() == Handedness::Right);
+}
+
+#[test]
+pub fn test_from_diagonal() {
+    assert!(Matrix4::from_diagonal(Point4D::new(1.0, 1.0, 1.0, 1.0)) == Matrix4::identity());
+
+    let m = Matrix4::from_diagonal(Point4D::new(2.0, 3.0, 4.0, 5.0));
+    let p = Point4D::new(1.0, -1.0, 0.5, 2.0);
+    assert!(p * m == Point4D::new(2.0, -3.0, 2.0, 10.0));
+}
+
+#[test]
+pub fn test_add_sub() {
+    let a = Matrix4::create_translation(1.0, 2.0, 3.0);
+    let b = Matrix4::create_scale(2.0, 2.0, 2.0);
+
+    let sum = a + b;
+    assert!(sum == Matrix4::new(3.0, 0.0, 0.0, 0.0,
+                                0.0, 3.0, 0.0, 0.0,
+                                0.0, 0.0, 3.0, 0.0,
+                                1.0, 2.0, 3.0, 2.0));
+    assert!(sum - b == a);
+    assert!(a - a == Matrix4::identity().mul_s(0.0));
+    assert!(a + a == a.mul_s(2.0));
+}
+
+#[test]
+pub fn test_from_nested_array() {
+    let rows = [[1.0, 2.0, 3.0, 4.0],
+                [5.0, 6.0, 7.0, 8.0],
+                [9.0, 10.0, 11.0, 12.0],
+                [13.0, 14.0, 15.0, 16.0]];
+    let m = Matrix4::from(rows);
+    assert!(m.m23 == 7.0);
+    assert!(m.m41 == 13.0);
+    assert!(m.to_2d_array_nested() == rows);
+
+    let t = Matrix4::create_translation(1.0, 2.0, 3.0);
+    assert!(Matrix4::from(t.to_2d_array_nested()) == t);
+}
+
+#[test]
+pub fn test_exactly_eq() {
+    let m = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.3).translate(1.0, 2.0, 0.0);
+    let round_trip = m.invert().invert();
+    assert!(round_trip.approx_eq(&m));
+    assert!(!round_trip.exactly_eq(&m));
+    assert!(m.exactly_eq(&m));
+}
+
+#[test]
+pub fn test_create_rotation_normalized() {
+    let m = Matrix4::create_rotation_normalized(0.0, 0.0, 5.0, 0.7);
+    assert!(m.approx_eq(&Matrix4::create_rotation(0.0, 0.0, 1.0, 0.7)));
+
+    // The rows of the linear part stay an orthonormal basis.
+    let m = Matrix4::create_rotation_normalized(1.0, 2.0, -3.0, 1.1);
+    let rows = [Point3D::new(m.m11, m.m12, m.m13),
+                Point3D::new(m.m21, m.m22, m.m23),
+                Point3D::new(m.m31, m.m32, m.m33)];
+    for i in 0..3 {
+        for j in 0..3 {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!(rows[i].dot(rows[j]).approx_eq(&expected));
+        }
+    }
+    assert!(m.determinant().approx_eq(&1.0));
+
+    assert!(Matrix4::create_rotation_normalized(0.0, 0.0, 0.0, 1.0) == Matrix4::identity());
+}
+
+#[test]
+pub fn test_transform_direction3d() {
+    let v = Point3D::new(1.0, -2.0, 0.5);
+    let t = Matrix4::create_translation(10.0, 20.0, 30.0);
+    assert!(t.transform_direction3d(&v) == v);
+
+    let m = Matrix4::create_scale(2.0, 3.0, 4.0).translate(5.0, 5.0, 5.0);
+    assert!(m.transform_direction3d(&v) == Point3D::new(2.0, -6.0, 2.0));
+}
+
+#[test]
+pub fn test_create_basis() {
+    let (right, up, forward) = (Point3D::new(0.0, 0.0, -1.0),
+                                Point3D::new(0.0, 1.0, 0.0),
+                                Point3D::new(1.0, 0.0, 0.0));
+    let m = Matrix4::create_basis(&right, &up, &forward);
+    assert!(Point3D::new(m.m11, m.m12, m.m13) == right);
+    assert!(Point3D::new(m.m21, m.m22, m.m23) == up);
+    assert!(Point3D::new(m.m31, m.m32, m.m33) == forward);
+
+    assert!(m.transform_direction3d(&Point3D::new(0.0, 0.0, 1.0)) == forward);
+    assert!(m.determinant().approx_eq(&1.0));
+}
+
+#[test]
+pub fn test_interpolate() {
+    let a = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.2);
+    let b = Matrix4::create_rotation(0.0, 0.0, 1.0, 1.4);
+    let mid = a.interpolate(&b, 0.5).unwrap();
+    assert!(mid.approx_eq(&Matrix4::create_rotation(0.0, 0.0, 1.0, 0.8)));
+    assert!(a.interpolate(&b, 0.0).unwrap().approx_eq(&a));
+    assert!(a.interpolate(&b, 1.0).unwrap().approx_eq(&b));
+
+    // From a uniform scale to nearly a half turn about y plus a translation.
+    let a = Matrix4::create_scale(2.0, 2.0, 2.0);
+    let b = Matrix4::create_translation(4.0, 0.0, 0.0)
+        .mul(&Matrix4::create_rotation(0.0, 1.0, 0.0, 3.0));
+    let quarter = a.interpolate(&b, 0.25).unwrap();
+    let expected = Matrix4::create_translation(1.0, 0.0, 0.0)
+        .mul(&Matrix4::create_rotation(0.0, 1.0, 0.0, 0.75))
+        .mul(&Matrix4::create_scale(1.75, 1.75, 1.75));
+    assert!(quarter.approx_eq(&expected));
+
+    // Rotating then scaling non-uniformly shears the rows; the endpoints still come back.
+    let sheared = Matrix4::create_scale(2.0, 1.0, 1.0)
+        .mul(&Matrix4::create_rotation(0.0, 0.0, 1.0, 0.5));
+    let skewed = Matrix4::create_skew(0.3, 0.0).translate(1.0, 2.0, 3.0);
+    assert!(sheared.interpolate(&skewed, 0.0).unwrap().approx_eq(&sheared));
+    assert!(sheared.interpolate(&skewed, 1.0).unwrap().approx_eq(&skewed));
+
+    // So do mirrors.
+    let mirror = Matrix4::create_scale(-1.0, 1.0, 1.0)
+        .mul(&Matrix4::create_rotation(1.0, 0.0, 0.0, 0.4));
+    assert!(mirror.interpolate(&a, 0.0).unwrap().approx_eq(&mirror));
+
+    assert!(a.interpolate(&Matrix4::create_perspective(100.0), 0.5).is_none());
+}
+
+#[test]
+pub fn test_transform_ray() {
+    let (origin, dir) = (Point3D::new(1.0, 2.0, 3.0), Point3D::new(0.0, 0.0, -1.0));
+
+    let t = Matrix4::create_translation(10.0, 0.0, -5.0);
+    assert!(t.transform_ray(&origin, &dir) == (Point3D::new(11.0, 2.0, -2.0), dir));
+
+    // A quarter turn about x.
+    let r = Matrix4::create_rotation(1.0, 0.0, 0.0, ::std::f32::consts::FRAC_PI_2);
+    let (o, d) = r.transform_ray(&origin, &dir);
+    assert!((o - Point3D::new(1.0, 3.0, -2.0)).approx_is_zero(1e-6));
+    assert!((d - Point3D::new(0.0, -1.0, 0.0)).approx_is_zero(1e-6));
+}
+
+#[test]
+pub fn test_diff_mask() {
+    let a = Matrix4::create_translation(1.0, 2.0, 3.0);
+    assert!(a.diff_mask(&a, 0.0) == [false; 16]);
+
+    let mut b = a;
+    b.m42 += 0.01;
+    let mut expected = [false; 16];
+    expected[13] = true;
+    assert!(a.diff_mask(&b, 1e-3) == expected);
+    assert!(a.diff_mask(&b, 0.1) == [false; 16]);
+}
+
+#[test]
+pub fn test_invert_general() {
+    let m = Matrix4::new( 2.0, -1.0,  0.5,  0.0,
+                          0.0,  3.0,  1.0, -0.25,
+                          1.5,  0.0, -2.0,  0.0,
+                         10.0, -4.0,  2.0,  1.0);
+    let inv = m.invert();
+
+    assert!(m.mul(&inv).approx_eq(&Matrix4::identity()));
+    assert!(inv.mul(&m).approx_eq(&Matrix4::identity()));
+    assert!(inv.invert().max_abs_diff(&m) < 1.0e-5);
+    assert!((inv.determinant() * m.determinant()).approx_eq(&1.0));
+
+    let rotation = Matrix4::create_rotation(0.0, 0.6, 0.8, 1.2).translate(3.0, -1.0, 2.0);
+    assert!(rotation.mul(&rotation.invert()).approx_eq(&Matrix4::identity()));
+
+    assert!(Matrix4::create_scale(1.0, 0.0, 1.0).invert() == Matrix4::identity());
+}
+
+#[test]
+pub fn test_point4d_mul() {
+    let m = Matrix4::create_perspective(50.0).mul(&Matrix4::create_rotation(0.0, 1.0, 0.0, 0.5))
+                                             .translate(1.0, 2.0, 3.0);
+
+    let p = Point4D::new(3.0, -2.0, 5.0, 1.0);
+    let (a, b) = (p * m, m.transform_point4d(&p));
+    assert!(a.x.approx_eq(&b.x) && a.y.approx_eq(&b.y) && a.z.approx_eq(&b.z) &&
+            a.w.approx_eq(&b.w));
+
+    // The same point with every component doubled is the same homogeneous point.
+    let q = Point4D::new(6.0, -4.0, 10.0, 2.0) * m;
+    assert!(q.x.approx_eq(&(a.x * 2.0)) && q.y.approx_eq(&(a.y * 2.0)) &&
+            q.z.approx_eq(&(a.z * 2.0)) && q.w.approx_eq(&(a.w * 2.0)));
+
+    // Direction vectors (w = 0) are not translated.
+    let t = Point4D::new(1.0, 1.0, 1.0, 0.0) * Matrix4::create_translation(5.0, 6.0, 7.0);
+    assert!(t == Point4D::new(1.0, 1.0, 1.0, 0.0));
+}
+
+#[test]
+pub fn test_checked_mul_s() {
+    let m = Matrix4::create_translation(1.0, 2.0, 3.0);
+    assert!(m.checked_mul_s(2.0) == Some(m.mul_s(2.0)));
+    assert!(m.checked_mul_s(::std::f32::INFINITY).is_none());
+    assert!(m.checked_mul_s(::std::f32::NAN).is_none());
+
+    // A finite scale can still overflow.
+    assert!(Matrix4::create_scale(1.0e30, 1.0, 1.0).checked_mul_s(1.0e30).is_none());
+}
+
+#[test]
+pub fn test_approx_eq_2d() {
+    let m1 = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.4).translate(10.0, 20.0, 0.0);
+    let m2 = Matrix4::create_perspective(100.0).mul(&m1).mul(&Matrix4::create_scale(1.0, 1.0, 3.0))
+                                                        .translate(0.0, 0.0, 42.0);
+
+    assert!(!m1.approx_eq(&m2));
+    assert!(m1.approx_eq_2d(&m2));
+    assert!(!m1.approx_eq_2d(&m1.translate(0.5, 0.0, 0.0)));
+}
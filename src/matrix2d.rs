@@ -7,6 +7,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use matrix::Matrix4;
 use num::{One, Zero};
 use point::Point2D;
 use rect::Rect;
@@ -110,3 +111,13 @@ impl<T:Add<T, Output=T> +
                   Size2D::new(max_x - min_x, max_y - min_y))
     }
 }
+
+impl Matrix2D<f32> {
+    /// Embeds this 2d transform in a 3d matrix that leaves the z and w components unchanged.
+    pub fn to_matrix4(&self) -> Matrix4 {
+        Matrix4::new(self.m11, self.m12, 0.0, 0.0,
+                     self.m21, self.m22, 0.0, 0.0,
+                     0.0,      0.0,      1.0, 0.0,
+                     self.m31, self.m32, 0.0, 1.0)
+    }
+}
@@ -9,6 +9,7 @@
 //! A one-dimensional length, tagged with its units.
 
 use std::num;
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 
 pub trait Zero {
@@ -27,3 +28,145 @@ impl<T: num::One> One for T {
     fn one() -> T { num::One::one() }
 }
 
+/// The floating point operations needed by the geometry types, so that methods can be written
+/// once for both `f32` and `f64`.
+pub trait Float: Copy + PartialOrd + Zero + One +
+                 Add<Self, Output=Self> + Sub<Self, Output=Self> +
+                 Mul<Self, Output=Self> + Div<Self, Output=Self> +
+                 Neg<Output=Self> {
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+}
+
+impl Float for f32 {
+    #[inline]
+    fn sqrt(self) -> f32 { f32::sqrt(self) }
+    #[inline]
+    fn sin(self) -> f32 { f32::sin(self) }
+    #[inline]
+    fn cos(self) -> f32 { f32::cos(self) }
+    #[inline]
+    fn tan(self) -> f32 { f32::tan(self) }
+    #[inline]
+    fn atan2(self, other: f32) -> f32 { f32::atan2(self, other) }
+    #[inline]
+    fn floor(self) -> f32 { f32::floor(self) }
+    #[inline]
+    fn ceil(self) -> f32 { f32::ceil(self) }
+    #[inline]
+    fn round(self) -> f32 { f32::round(self) }
+}
+
+impl Float for f64 {
+    #[inline]
+    fn sqrt(self) -> f64 { f64::sqrt(self) }
+    #[inline]
+    fn sin(self) -> f64 { f64::sin(self) }
+    #[inline]
+    fn cos(self) -> f64 { f64::cos(self) }
+    #[inline]
+    fn tan(self) -> f64 { f64::tan(self) }
+    #[inline]
+    fn atan2(self, other: f64) -> f64 { f64::atan2(self, other) }
+    #[inline]
+    fn floor(self) -> f64 { f64::floor(self) }
+    #[inline]
+    fn ceil(self) -> f64 { f64::ceil(self) }
+    #[inline]
+    fn round(self) -> f64 { f64::round(self) }
+}
+
+/// A marker for the primitive integer types, for methods that only make sense on whole
+/// numbers, such as walking the lattice points of a rectangle.
+pub trait Int: Copy + PartialOrd + Zero + One +
+               Add<Self, Output=Self> + Sub<Self, Output=Self> {}
+
+impl Int for i8 {}
+impl Int for i16 {}
+impl Int for i32 {}
+impl Int for i64 {}
+impl Int for isize {}
+impl Int for u8 {}
+impl Int for u16 {}
+impl Int for u32 {}
+impl Int for u64 {}
+impl Int for usize {}
+
+/// Rounding to the nearest multiple of a step, for `Point2D::snap_to_grid`. Integers are
+/// snapped exactly with checked arithmetic rather than through `f64`, which cannot represent
+/// every `i64` or `u64`.
+pub trait Snap: Copy {
+    /// Returns the multiple of `step` nearest to `self`, with ties rounded away from zero, or
+    /// `None` if `step` is zero or the result does not fit in `Self`.
+    fn snap(self, step: Self) -> Option<Self>;
+}
+
+macro_rules! impl_snap_int {
+    ($($t:ty),*) => {$(
+        impl Snap for $t {
+            #[inline]
+            fn snap(self, step: $t) -> Option<$t> {
+                // Every value is a multiple of a unit step, including `MIN` for a step of -1,
+                // where the checked division below would fail.
+                if step.abs_diff(0) == 1 {
+                    return Some(self);
+                }
+                let q = self.checked_div(step)?;
+                let r = self % step;
+                let r_size = r.abs_diff(0);
+                if r_size < step.abs_diff(0) - r_size {
+                    return q.checked_mul(step);
+                }
+                // `q` was truncated toward zero; step one further from it.
+                #[allow(unused_comparisons)]
+                let q = if (r < 0) == (step < 0) { q.checked_add(1)? } else { q.checked_sub(1)? };
+                q.checked_mul(step)
+            }
+        }
+    )*}
+}
+
+impl_snap_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_snap_float {
+    ($($t:ty),*) => {$(
+        impl Snap for $t {
+            #[inline]
+            fn snap(self, step: $t) -> Option<$t> {
+                if step == 0.0 {
+                    return None;
+                }
+                let (v, step) = (self as f64, step as f64);
+                Some(((v / step).round() * step) as $t)
+            }
+        }
+    )*}
+}
+
+impl_snap_float!(f32, f64);
+
+#[test]
+fn test_float_f32() {
+    fn check<T: Float>(x: T, y: T) -> (T, T, T, T, T) {
+        (Float::sqrt(x), Float::sin(x), Float::cos(x), Float::tan(x), Float::atan2(x, y))
+    }
+
+    let (x, y) = (0.7f32, -1.3f32);
+    assert!(check(x, y) == (x.sqrt(), x.sin(), x.cos(), x.tan(), x.atan2(y)));
+}
+
+#[test]
+fn test_float_f64() {
+    fn check<T: Float>(x: T, y: T) -> (T, T, T, T, T) {
+        (Float::sqrt(x), Float::sin(x), Float::cos(x), Float::tan(x), Float::atan2(x, y))
+    }
+
+    let (x, y) = (2.5f64, 0.4f64);
+    assert!(check(x, y) == (x.sqrt(), x.sin(), x.cos(), x.tan(), x.atan2(y)));
+}
@@ -7,13 +7,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use approxeq::ApproxEq;
 use length::Length;
 use size::Size2D;
-use num::Zero;
+use num::{Float, One, Snap, Zero};
+use rect::{max, min};
 
 use num_lib::NumCast;
 use std::fmt::{self, Formatter};
-use std::ops::{Add, Neg, Mul, Sub, Div};
+use std::array;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::iter::{IntoIterator, Sum};
+use std::ops::{Add, Neg, Mul, Sub, Div, Rem};
 
 #[derive(Clone, Copy, RustcDecodable, RustcEncodable, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "plugins", derive(HeapSizeOf, Deserialize, Serialize))]
@@ -28,6 +34,13 @@ impl<T: Zero> Point2D<T> {
     }
 }
 
+impl<T: Zero> Zero for Point2D<T> {
+    #[inline]
+    fn zero() -> Point2D<T> {
+        Point2D::zero()
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Point2D<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({:?},{:?})", self.x, self.y)
@@ -46,6 +59,42 @@ impl<T> Point2D<T> {
     }
 }
 
+impl<T: Clone> Point2D<T> {
+    /// Creates a point with every component set to `v`.
+    #[inline]
+    pub fn splat(v: T) -> Point2D<T> {
+        Point2D::new(v.clone(), v)
+    }
+}
+
+impl<T: Copy> Point2D<T> {
+    /// Returns an iterator over the components, in `x`, `y` order.
+    #[inline]
+    pub fn components(&self) -> array::IntoIter<T, 2> {
+        self.into_iter()
+    }
+}
+
+impl<T> IntoIterator for Point2D<T> {
+    type Item = T;
+    type IntoIter = array::IntoIter<T, 2>;
+
+    /// Yields the components in `x`, `y` order.
+    #[inline]
+    fn into_iter(self) -> array::IntoIter<T, 2> {
+        IntoIterator::into_iter([self.x, self.y])
+    }
+}
+
+impl<T: Clone> Point2D<T> {
+    /// Returns this point as a displacement vector from the origin. `Point2D` already serves
+    /// as the vector type, so this is the identity, but it makes the intent explicit.
+    #[inline]
+    pub fn to_vector(&self) -> Point2D<T> {
+        self.clone()
+    }
+}
+
 impl<T: Mul<T, Output=T> +
         Add<T, Output=T> +
         Sub<T, Output=T> +
@@ -56,10 +105,185 @@ impl<T: Mul<T, Output=T> +
         self.y * other.y
     }
 
+    /// Returns the z component of the 3d cross product of two vectors in the xy plane, which
+    /// is the signed area of the parallelogram they span.
     #[inline]
     pub fn cross(self, other: Point2D<T>) -> T {
         self.x * other.y - self.y * other.x
     }
+
+    /// Scales this point's offset from `pivot` by `factor`, moving it away from the pivot for
+    /// factors above one and toward it below.
+    #[inline]
+    pub fn scale_around(self, pivot: Point2D<T>, factor: T) -> Point2D<T> {
+        pivot + (self - pivot) * factor
+    }
+}
+
+impl<T: Float> Point2D<T> {
+    /// Linearly interpolates between this point (`t = 0`) and `other` (`t = 1`). Values of
+    /// `t` outside `[0, 1]` extrapolate.
+    #[inline]
+    pub fn lerp(self, other: Point2D<T>, t: T) -> Point2D<T> {
+        self + (other - self) * t
+    }
+
+    /// Like `lerp`, but with `t` clamped into `[0, 1]` so the result stays between the two
+    /// points.
+    #[inline]
+    pub fn lerp_clamped(self, other: Point2D<T>, t: T) -> Point2D<T> {
+        self.lerp(other, clamp01(t))
+    }
+
+    /// Returns the projection of this vector onto `axis`, which must be non-zero.
+    #[inline]
+    pub fn project_onto(self, axis: Point2D<T>) -> Point2D<T> {
+        axis * (self.dot(axis) / axis.dot(axis))
+    }
+
+    /// Returns the component of this vector perpendicular to `axis`, so that
+    /// `v.project_onto(axis) + v.reject_from(axis)` is `v`.
+    #[inline]
+    pub fn reject_from(self, axis: Point2D<T>) -> Point2D<T> {
+        self - self.project_onto(axis)
+    }
+
+    /// Like `lerp`, but with `t` remapped by the easing function `ease` first, e.g.
+    /// `|t| t * t` to ease in.
+    #[inline]
+    pub fn interpolate_with<F: Fn(T) -> T>(self, other: Point2D<T>, t: T, ease: F) -> Point2D<T> {
+        self.lerp(other, ease(t))
+    }
+}
+
+impl<T: Float + ApproxEq<T>> Point2D<T> {
+    /// Returns the Euclidean length of this vector.
+    #[inline]
+    pub fn length(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns this vector scaled to unit length, or `None` if it is too close to zero to have
+    /// a direction.
+    #[inline]
+    pub fn try_normalize(self) -> Option<Point2D<T>> {
+        let length = self.length();
+        if length.approx_eq(&Zero::zero()) {
+            None
+        } else {
+            Some(self / length)
+        }
+    }
+
+    /// Returns this vector scaled to unit length, or `fallback` if it is too close to zero to
+    /// have a direction.
+    #[inline]
+    pub fn normalize_or(self, fallback: Point2D<T>) -> Point2D<T> {
+        self.try_normalize().unwrap_or(fallback)
+    }
+
+    /// Returns the point on the segment from `a` to `b` closest to this point. If `a == b` the
+    /// segment is a single point and `a` is returned.
+    pub fn closest_on_segment(self, a: Point2D<T>, b: Point2D<T>) -> Point2D<T> {
+        let ab = b - a;
+        let length_squared = ab.dot(ab);
+        if length_squared == Zero::zero() {
+            return a;
+        }
+        let t = clamp01((self - a).dot(ab) / length_squared);
+        a + ab * t
+    }
+}
+
+impl Point2D<f32> {
+    /// Orders points lexicographically by `x` then `y`, comparing components with
+    /// `f32::total_cmp`. Unlike `PartialOrd` this is a total order, so NaN coordinates sort
+    /// deterministically (positive NaN after every number, negative NaN before). It also
+    /// puts `-0.0` before `0.0`.
+    pub fn total_cmp(&self, other: &Point2D<f32>) -> Ordering {
+        self.x.total_cmp(&other.x).then_with(|| self.y.total_cmp(&other.y))
+    }
+
+    /// Rotates this direction toward `target` by at most `max_step` radians, the shorter way
+    /// round. If `target` is within `max_step`, it is returned as is, so repeated calls
+    /// settle exactly on it.
+    ///
+    /// Both are expected to have the same length, typically one; a partial step keeps the
+    /// length of `self`.
+    pub fn rotate_toward(self, target: Point2D<f32>, max_step: f32) -> Point2D<f32> {
+        let angle = self.cross(target).atan2(self.dot(target));
+        if angle.abs() <= max_step {
+            return target;
+        }
+        let (sin, cos) = (max_step * angle.signum()).sin_cos();
+        Point2D::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Returns the barycentric coordinates `(u, v, w)` of `p` with respect to the triangle
+    /// `a`, `b`, `c`, so that `p == a * u + b * v + c * w` and `u + v + w == 1`. All three
+    /// weights are in `[0, 1]` exactly when `p` is inside the triangle or on its edges.
+    ///
+    /// If the triangle is degenerate (its vertices are collinear or coincide), there is no
+    /// unique answer and all three weights are NaN.
+    pub fn barycentric(p: Point2D<f32>, a: Point2D<f32>, b: Point2D<f32>, c: Point2D<f32>)
+                       -> (f32, f32, f32) {
+        let area = (b - a).cross(c - a);
+        if area == 0.0 {
+            return (::std::f32::NAN, ::std::f32::NAN, ::std::f32::NAN);
+        }
+        let v = (p - a).cross(c - a) / area;
+        let w = (b - a).cross(p - a) / area;
+        (1.0 - v - w, v, w)
+    }
+}
+
+impl<T: Zero + PartialEq> Point2D<T> {
+    /// Returns true if every component is exactly zero.
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        let zero: T = Zero::zero();
+        self.x == zero && self.y == zero
+    }
+}
+
+impl<T: Zero + ApproxEq<T>> Point2D<T> {
+    /// Returns true if every component is within `eps` of zero.
+    #[inline]
+    pub fn approx_is_zero(&self, eps: T) -> bool {
+        let zero: T = Zero::zero();
+        self.x.approx_eq_eps(&zero, &eps) &&
+        self.y.approx_eq_eps(&zero, &eps)
+    }
+}
+
+impl<T: Copy + PartialOrd> Point2D<T> {
+    /// Returns the componentwise minimum of two points.
+    #[inline]
+    pub fn min(self, other: Point2D<T>) -> Point2D<T> {
+        Point2D::new(min(self.x, other.x), min(self.y, other.y))
+    }
+
+    /// Returns the componentwise maximum of two points.
+    #[inline]
+    pub fn max(self, other: Point2D<T>) -> Point2D<T> {
+        Point2D::new(max(self.x, other.x), max(self.y, other.y))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + Neg<Output=T>> Point2D<T> {
+    /// Returns the point with the absolute value of each component.
+    #[inline]
+    pub fn abs(self) -> Point2D<T> {
+        Point2D::new(abs(self.x), abs(self.y))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + One> Point2D<T> {
+    /// Clamps each component into the unit interval `[0, 1]`.
+    #[inline]
+    pub fn clamp01(self) -> Point2D<T> {
+        Point2D::new(clamp01(self.x), clamp01(self.y))
+    }
 }
 
 impl<T:Clone + Add<T, Output=T>> Add for Point2D<T> {
@@ -82,6 +306,12 @@ impl<T: Copy + Add<T, Output=T>> Point2D<T> {
     }
 }
 
+impl<T: Clone + Add<T, Output=T> + Zero> Sum for Point2D<T> {
+    fn sum<I: Iterator<Item=Point2D<T>>>(iter: I) -> Point2D<T> {
+        iter.fold(Point2D::zero(), Add::add)
+    }
+}
+
 impl<T:Clone + Sub<T, Output=T>> Sub for Point2D<T> {
     type Output = Point2D<T>;
     fn sub(self, other: Point2D<T>) -> Point2D<T> {
@@ -113,6 +343,54 @@ impl<Scale: Copy, T0: Div<Scale, Output=T1>, T1: Clone> Div<Scale> for Point2D<T
     }
 }
 
+impl<T: Snap> Point2D<T> {
+    /// Snaps each component to the nearest multiple of `step`. Ties are rounded away from
+    /// zero, so with a step of 10 both 5 and -5 move away from the origin to 10 and -10.
+    ///
+    /// Returns `None` if `step` is zero, since there is no grid to snap to, or if a snapped
+    /// component does not fit in `T`.
+    pub fn snap_to_grid(self, step: T) -> Option<Point2D<T>> {
+        Some(Point2D::new(self.x.snap(step)?, self.y.snap(step)?))
+    }
+}
+
+impl<T: Neg<Output=T>> Point2D<T> {
+    /// Rotates by 90° clockwise in a y-down coordinate system (counter-clockwise if y points
+    /// up), using only a swap and a negation so integer points stay exact.
+    #[inline]
+    pub fn rotate_90_cw(self) -> Point2D<T> {
+        Point2D { x: -self.y, y: self.x }
+    }
+
+    /// Rotates by 180° about the origin.
+    #[inline]
+    pub fn rotate_180(self) -> Point2D<T> {
+        Point2D { x: -self.x, y: -self.y }
+    }
+
+    /// Rotates by 270° clockwise in a y-down coordinate system, i.e. 90° the other way.
+    #[inline]
+    pub fn rotate_270_cw(self) -> Point2D<T> {
+        Point2D { x: self.y, y: -self.x }
+    }
+}
+
+impl<T: Copy + Rem<T, Output=T>> Rem<T> for Point2D<T> {
+    type Output = Point2D<T>;
+    #[inline]
+    fn rem(self, modulus: T) -> Point2D<T> {
+        Point2D::new(self.x % modulus, self.y % modulus)
+    }
+}
+
+impl<T: Rem<T, Output=T>> Rem<Point2D<T>> for Point2D<T> {
+    type Output = Point2D<T>;
+    #[inline]
+    fn rem(self, modulus: Point2D<T>) -> Point2D<T> {
+        Point2D::new(self.x % modulus.x, self.y % modulus.y)
+    }
+}
+
 // Convenient aliases for Point2D with typed units
 
 pub type TypedPoint2D<Unit, T> = Point2D<Length<Unit, T>>;
@@ -154,6 +432,14 @@ impl<Unit, T: NumCast + Clone> Point2D<Length<Unit, T>> {
     }
 }
 
+impl<Unit> Point2D<Length<Unit, f32>> {
+    /// Returns the magnitude of this typed vector as a length in the same unit.
+    #[inline]
+    pub fn length_typed(self) -> Length<Unit, f32> {
+        Length::new(self.to_untyped().length())
+    }
+}
+
 #[derive(Clone, Copy, RustcDecodable, RustcEncodable, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "plugins", derive(HeapSizeOf))]
 pub struct Point3D<T> {
@@ -169,6 +455,13 @@ impl<T: Zero> Point3D<T> {
     }
 }
 
+impl<T: Zero> Zero for Point3D<T> {
+    #[inline]
+    fn zero() -> Point3D<T> {
+        Point3D::zero()
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Point3D<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({:?},{:?},{:?})", self.x, self.y, self.z)
@@ -188,6 +481,33 @@ impl<T> Point3D<T> {
     }
 }
 
+impl<T: Clone> Point3D<T> {
+    /// Creates a point with every component set to `v`.
+    #[inline]
+    pub fn splat(v: T) -> Point3D<T> {
+        Point3D::new(v.clone(), v.clone(), v)
+    }
+}
+
+impl<T: Copy> Point3D<T> {
+    /// Returns an iterator over the components, in `x`, `y`, `z` order.
+    #[inline]
+    pub fn components(&self) -> array::IntoIter<T, 3> {
+        self.into_iter()
+    }
+}
+
+impl<T> IntoIterator for Point3D<T> {
+    type Item = T;
+    type IntoIter = array::IntoIter<T, 3>;
+
+    /// Yields the components in `x`, `y`, `z` order.
+    #[inline]
+    fn into_iter(self) -> array::IntoIter<T, 3> {
+        IntoIterator::into_iter([self.x, self.y, self.z])
+    }
+}
+
 impl<T: Mul<T, Output=T> +
         Add<T, Output=T> +
         Sub<T, Output=T> +
@@ -199,6 +519,7 @@ impl<T: Mul<T, Output=T> +
         self.z * other.z
     }
 
+    /// Returns the cross product, a vector perpendicular to both inputs.
     #[inline]
     pub fn cross(self, other: Point3D<T>) -> Point3D<T> {
         Point3D {
@@ -207,6 +528,166 @@ impl<T: Mul<T, Output=T> +
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    /// Scales this point's offset from `pivot` by `factor`, moving it away from the pivot for
+    /// factors above one and toward it below.
+    #[inline]
+    pub fn scale_around(self, pivot: Point3D<T>, factor: T) -> Point3D<T> {
+        pivot + (self - pivot) * factor
+    }
+}
+
+impl Point3D<f32> {
+    /// Orders points lexicographically by `x`, `y`, then `z`, with the same total order on
+    /// components as `Point2D::total_cmp`.
+    pub fn total_cmp(&self, other: &Point3D<f32>) -> Ordering {
+        self.x.total_cmp(&other.x)
+            .then_with(|| self.y.total_cmp(&other.y))
+            .then_with(|| self.z.total_cmp(&other.z))
+    }
+
+    /// Returns the orthogonal projection of this point onto the plane through `plane_point`
+    /// with normal `plane_normal`. The normal need not be unit length, but must be non-zero.
+    pub fn project_to_plane(self, plane_normal: Point3D<f32>, plane_point: Point3D<f32>)
+                            -> Point3D<f32> {
+        plane_point + (self - plane_point).reject_from(plane_normal)
+    }
+}
+
+impl<T: Zero + PartialEq> Point3D<T> {
+    /// Returns true if every component is exactly zero.
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        let zero: T = Zero::zero();
+        self.x == zero && self.y == zero && self.z == zero
+    }
+}
+
+impl<T: Zero + ApproxEq<T>> Point3D<T> {
+    /// Returns true if every component is within `eps` of zero.
+    #[inline]
+    pub fn approx_is_zero(&self, eps: T) -> bool {
+        let zero: T = Zero::zero();
+        self.x.approx_eq_eps(&zero, &eps) &&
+        self.y.approx_eq_eps(&zero, &eps) &&
+        self.z.approx_eq_eps(&zero, &eps)
+    }
+}
+
+impl<T: Float> Point3D<T> {
+    /// Linearly interpolates between this point (`t = 0`) and `other` (`t = 1`). Values of
+    /// `t` outside `[0, 1]` extrapolate.
+    #[inline]
+    pub fn lerp(self, other: Point3D<T>, t: T) -> Point3D<T> {
+        self + (other - self) * t
+    }
+
+    /// Like `lerp`, but with `t` clamped into `[0, 1]` so the result stays between the two
+    /// points.
+    #[inline]
+    pub fn lerp_clamped(self, other: Point3D<T>, t: T) -> Point3D<T> {
+        self.lerp(other, clamp01(t))
+    }
+
+    /// Returns the projection of this vector onto `axis`, which must be non-zero.
+    #[inline]
+    pub fn project_onto(self, axis: Point3D<T>) -> Point3D<T> {
+        axis * (self.dot(axis) / axis.dot(axis))
+    }
+
+    /// Returns the component of this vector perpendicular to `axis`, so that
+    /// `v.project_onto(axis) + v.reject_from(axis)` is `v`.
+    #[inline]
+    pub fn reject_from(self, axis: Point3D<T>) -> Point3D<T> {
+        self - self.project_onto(axis)
+    }
+
+    /// Returns the Euclidean length of this vector.
+    #[inline]
+    pub fn length(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the unsigned angle between two vectors, in radians.
+    ///
+    /// This is computed as `atan2(|a × b|, a · b)` rather than `acos` of the normalized dot
+    /// product, which loses precision for nearly parallel vectors.
+    #[inline]
+    pub fn angle_between(self, other: Point3D<T>) -> T {
+        self.cross(other).length().atan2(self.dot(other))
+    }
+
+    /// Converts this point to spherical coordinates `(radius, theta, phi)`, using the ISO
+    /// convention: `theta` is the polar angle from the +z axis in `[0, π]` and `phi` the
+    /// azimuth in the xy plane from the +x axis towards +y, in `(-π, π]`. Both are in radians.
+    #[inline]
+    pub fn to_spherical(self) -> (T, T, T) {
+        let radius = self.length();
+        let theta = (self.x * self.x + self.y * self.y).sqrt().atan2(self.z);
+        let phi = self.y.atan2(self.x);
+        (radius, theta, phi)
+    }
+
+    /// Creates a point from spherical coordinates, using the conventions of `to_spherical`.
+    #[inline]
+    pub fn from_spherical(radius: T, theta: T, phi: T) -> Point3D<T> {
+        Point3D::new(radius * theta.sin() * phi.cos(),
+                     radius * theta.sin() * phi.sin(),
+                     radius * theta.cos())
+    }
+}
+
+impl<T: Float + ApproxEq<T>> Point3D<T> {
+    /// Returns the cross product scaled to unit length, or `None` if the vectors are too close
+    /// to parallel (or zero) for it to have a direction.
+    #[inline]
+    pub fn try_cross_normalized(self, other: Point3D<T>) -> Option<Point3D<T>> {
+        let cross = self.cross(other);
+        let length = cross.length();
+        if length.approx_eq(&Zero::zero()) {
+            None
+        } else {
+            Some(cross / length)
+        }
+    }
+
+    /// Returns the cross product scaled to unit length, e.g. to build an orthonormal frame.
+    /// The result is NaN for parallel vectors; use `try_cross_normalized` to handle those.
+    #[inline]
+    pub fn cross_normalized(self, other: Point3D<T>) -> Point3D<T> {
+        let cross = self.cross(other);
+        cross / cross.length()
+    }
+}
+
+impl<T: Copy + PartialOrd> Point3D<T> {
+    /// Returns the componentwise minimum of two points.
+    #[inline]
+    pub fn min(self, other: Point3D<T>) -> Point3D<T> {
+        Point3D::new(min(self.x, other.x), min(self.y, other.y), min(self.z, other.z))
+    }
+
+    /// Returns the componentwise maximum of two points.
+    #[inline]
+    pub fn max(self, other: Point3D<T>) -> Point3D<T> {
+        Point3D::new(max(self.x, other.x), max(self.y, other.y), max(self.z, other.z))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + Neg<Output=T>> Point3D<T> {
+    /// Returns the point with the absolute value of each component.
+    #[inline]
+    pub fn abs(self) -> Point3D<T> {
+        Point3D::new(abs(self.x), abs(self.y), abs(self.z))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + One> Point3D<T> {
+    /// Clamps each component into the unit interval `[0, 1]`.
+    #[inline]
+    pub fn clamp01(self) -> Point3D<T> {
+        Point3D::new(clamp01(self.x), clamp01(self.y), clamp01(self.z))
+    }
 }
 
 impl<T:Clone + Add<T, Output=T>> Add for Point3D<T> {
@@ -218,6 +699,12 @@ impl<T:Clone + Add<T, Output=T>> Add for Point3D<T> {
     }
 }
 
+impl<T: Clone + Add<T, Output=T> + Zero> Sum for Point3D<T> {
+    fn sum<I: Iterator<Item=Point3D<T>>>(iter: I) -> Point3D<T> {
+        iter.fold(Point3D::zero(), Add::add)
+    }
+}
+
 impl<T:Clone + Sub<T, Output=T>> Sub for Point3D<T> {
     type Output = Point3D<T>;
     fn sub(self, other: Point3D<T>) -> Point3D<T> {
@@ -235,8 +722,32 @@ impl <T:Clone + Neg<Output=T>> Neg for Point3D<T> {
     }
 }
 
+impl<Scale: Copy, T0: Mul<Scale, Output=T1>, T1: Clone> Mul<Scale> for Point3D<T0> {
+    type Output = Point3D<T1>;
+    #[inline]
+    fn mul(self, scale: Scale) -> Point3D<T1> {
+        Point3D::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl<Scale: Copy, T0: Div<Scale, Output=T1>, T1: Clone> Div<Scale> for Point3D<T0> {
+    type Output = Point3D<T1>;
+    #[inline]
+    fn div(self, scale: Scale) -> Point3D<T1> {
+        Point3D::new(self.x / scale, self.y / scale, self.z / scale)
+    }
+}
+
+impl<Unit> Point3D<Length<Unit, f32>> {
+    /// Returns the magnitude of this typed vector as a length in the same unit.
+    #[inline]
+    pub fn length_typed(self) -> Length<Unit, f32> {
+        Length::new(Point3D::new(self.x.get(), self.y.get(), self.z.get()).length())
+    }
+}
+
 #[derive(Clone, Copy, RustcDecodable, RustcEncodable, Eq, Hash, PartialEq)]
-#[cfg_attr(feature = "plugins", derive(HeapSizeOf))]
+#[cfg_attr(feature = "plugins", derive(HeapSizeOf, Deserialize, Serialize))]
 pub struct Point4D<T> {
     pub x: T,
     pub y: T,
@@ -256,6 +767,13 @@ impl<T: Zero> Point4D<T> {
     }
 }
 
+impl<T: Zero> Zero for Point4D<T> {
+    #[inline]
+    fn zero() -> Point4D<T> {
+        Point4D::zero()
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Point4D<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({:?},{:?},{:?},{:?})", self.x, self.y, self.z, self.w)
@@ -273,6 +791,101 @@ impl<T> Point4D<T> {
     pub fn new(x: T, y: T, z: T, w: T) -> Point4D<T> {
         Point4D {x: x, y: y, z: z, w: w}
     }
+
+    /// Returns `(x, y, z)`, dropping `w` without dividing by it. For the 3d point a
+    /// homogeneous point represents, call `normalize_homogeneous` first.
+    #[inline]
+    pub fn xyz(self) -> Point3D<T> {
+        Point3D::new(self.x, self.y, self.z)
+    }
+
+    /// Creates a point from `[x, y, z, w]`.
+    #[inline]
+    pub fn from_array(array: [T; 4]) -> Point4D<T> {
+        let [x, y, z, w] = array;
+        Point4D::new(x, y, z, w)
+    }
+}
+
+impl<T: Clone> Point4D<T> {
+    /// Creates a point with every component set to `v`.
+    #[inline]
+    pub fn splat(v: T) -> Point4D<T> {
+        Point4D::new(v.clone(), v.clone(), v.clone(), v)
+    }
+
+    /// Returns the components as `[x, y, z, w]`.
+    #[inline]
+    pub fn to_array(&self) -> [T; 4] {
+        [self.x.clone(), self.y.clone(), self.z.clone(), self.w.clone()]
+    }
+}
+
+impl<T: Copy + PartialEq + Zero + One + Div<T, Output=T>> Point4D<T> {
+    /// Divides every component by `w`, so that the result has `w == 1` and represents the same
+    /// homogeneous point.
+    ///
+    /// A point with `w == 0` is at infinity and has no such representation; it is returned
+    /// unchanged.
+    #[inline]
+    pub fn normalize_homogeneous(self) -> Point4D<T> {
+        if self.w == Zero::zero() {
+            return self;
+        }
+        Point4D::new(self.x / self.w, self.y / self.w, self.z / self.w, One::one())
+    }
+}
+
+impl<T: Float> Point4D<T> {
+    /// Rounds each component down to an integer.
+    #[inline]
+    pub fn floor(self) -> Point4D<T> {
+        Point4D::new(self.x.floor(), self.y.floor(), self.z.floor(), self.w.floor())
+    }
+
+    /// Rounds each component up to an integer.
+    #[inline]
+    pub fn ceil(self) -> Point4D<T> {
+        Point4D::new(self.x.ceil(), self.y.ceil(), self.z.ceil(), self.w.ceil())
+    }
+
+    /// Rounds each component to the nearest integer, with halfway cases away from zero.
+    #[inline]
+    pub fn round(self) -> Point4D<T> {
+        Point4D::new(self.x.round(), self.y.round(), self.z.round(), self.w.round())
+    }
+}
+
+impl<T: Copy + PartialOrd> Point4D<T> {
+    /// Returns the componentwise minimum of two points.
+    #[inline]
+    pub fn min(self, other: Point4D<T>) -> Point4D<T> {
+        Point4D::new(min(self.x, other.x), min(self.y, other.y),
+                     min(self.z, other.z), min(self.w, other.w))
+    }
+
+    /// Returns the componentwise maximum of two points.
+    #[inline]
+    pub fn max(self, other: Point4D<T>) -> Point4D<T> {
+        Point4D::new(max(self.x, other.x), max(self.y, other.y),
+                     max(self.z, other.z), max(self.w, other.w))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + Neg<Output=T>> Point4D<T> {
+    /// Returns the point with the absolute value of each component.
+    #[inline]
+    pub fn abs(self) -> Point4D<T> {
+        Point4D::new(abs(self.x), abs(self.y), abs(self.z), abs(self.w))
+    }
+}
+
+impl<T: Copy + PartialOrd + Zero + One> Point4D<T> {
+    /// Clamps each component into the unit interval `[0, 1]`.
+    #[inline]
+    pub fn clamp01(self) -> Point4D<T> {
+        Point4D::new(clamp01(self.x), clamp01(self.y), clamp01(self.z), clamp01(self.w))
+    }
 }
 
 impl<T:Clone + Add<T, Output=T>> Add for Point4D<T> {
@@ -303,6 +916,119 @@ impl <T:Clone + Neg<Output=T>> Neg for Point4D<T> {
     }
 }
 
+/// A `Point2D<f32>` that can be hashed and compared exactly, for example to deduplicate mesh
+/// vertices through a `HashSet`.
+///
+/// Components are compared by bit pattern after mapping `-0.0` to `0.0` and every NaN to a
+/// single NaN. That means points that differ only by rounding error are different keys, and
+/// that NaN components are equal to each other here even though `NaN != NaN` as `f32`.
+#[derive(Clone, Copy, Debug)]
+pub struct HashablePoint2D(pub Point2D<f32>);
+
+impl HashablePoint2D {
+    fn bits(&self) -> (u32, u32) {
+        (canonical_bits(self.0.x), canonical_bits(self.0.y))
+    }
+}
+
+impl PartialEq for HashablePoint2D {
+    fn eq(&self, other: &HashablePoint2D) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for HashablePoint2D {}
+
+impl Hash for HashablePoint2D {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state)
+    }
+}
+
+/// A `Point3D<f32>` that can be hashed and compared exactly. See `HashablePoint2D`.
+#[derive(Clone, Copy, Debug)]
+pub struct HashablePoint3D(pub Point3D<f32>);
+
+impl HashablePoint3D {
+    fn bits(&self) -> (u32, u32, u32) {
+        (canonical_bits(self.0.x), canonical_bits(self.0.y), canonical_bits(self.0.z))
+    }
+}
+
+impl PartialEq for HashablePoint3D {
+    fn eq(&self, other: &HashablePoint3D) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for HashablePoint3D {}
+
+impl Hash for HashablePoint3D {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state)
+    }
+}
+
+/// Returns the signed area of the parallelogram spanned by `a` and `b`, the same as
+/// `a.cross(b)`. It is positive when `b` is counter-clockwise from `a` in a y-up space (and
+/// so clockwise on a y-down screen), negative the other way, and zero if they are parallel.
+#[inline]
+pub fn signed_parallelogram_area<T>(a: Point2D<T>, b: Point2D<T>) -> T
+    where T: Mul<T, Output=T> + Add<T, Output=T> + Sub<T, Output=T> + Copy {
+    a.cross(b)
+}
+
+/// Returns true if the closed polygon through `points` is convex, in either winding order:
+/// the cross products of all consecutive edges share a sign.
+///
+/// Collinear vertices, whose cross product is zero, don't break convexity, so a square with
+/// an extra point in the middle of an edge is convex. Fewer than three points, or points that
+/// all lie on one line, don't make a polygon and return false. The check is local to each
+/// vertex, so a polygon that winds around more than once, like a pentagram, is not detected.
+pub fn is_convex(points: &[Point2D<f32>]) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+    let mut sign = 0.0;
+    for (i, &p) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        let after = points[(i + 2) % points.len()];
+        let turn = (next - p).cross(after - next);
+        if turn == 0.0 {
+            continue;
+        }
+        if sign == 0.0 {
+            sign = turn.signum();
+        } else if turn.signum() != sign {
+            return false;
+        }
+    }
+    sign != 0.0
+}
+
+#[inline]
+fn canonical_bits(x: f32) -> u32 {
+    let x = if x == 0.0 { 0.0 } else if x.is_nan() { ::std::f32::NAN } else { x };
+    x.to_bits()
+}
+
+#[inline]
+fn abs<T: PartialOrd + Zero + Neg<Output=T>>(x: T) -> T {
+    if x < Zero::zero() { -x } else { x }
+}
+
+#[inline]
+fn clamp01<T: PartialOrd + Zero + One>(x: T) -> T {
+    let (zero, one): (T, T) = (Zero::zero(), One::one());
+    if x < zero {
+        zero
+    } else if x > one {
+        one
+    } else {
+        x
+    }
+}
+
 #[test]
 pub fn test_dot_2d() {
     let p1 = Point2D::new(2.0, 7.0);
@@ -334,3 +1060,455 @@ pub fn test_cross_3d() {
     assert!(p3.y == 105.0);
     assert!(p3.z == -59.0);
 }
+
+#[test]
+pub fn test_clamp01() {
+    let p2 = Point2D::new(-0.5f32, 1.5).clamp01();
+    assert!(p2 == Point2D::new(0.0, 1.0));
+
+    let p3 = Point3D::new(0.25f32, -3.0, 7.0).clamp01();
+    assert!(p3 == Point3D::new(0.25, 0.0, 1.0));
+
+    let p4 = Point4D::new(2.0f64, 0.5, -0.1, 1.0).clamp01();
+    assert!(p4 == Point4D::new(1.0, 0.5, 0.0, 1.0));
+}
+
+#[test]
+pub fn test_project_reject_2d() {
+    let v = Point2D::new(3.0, 4.0);
+    let axis = Point2D::new(2.0, 0.0);
+    assert!(v.project_onto(axis) == Point2D::new(3.0, 0.0));
+    assert!(v.reject_from(axis) == Point2D::new(0.0, 4.0));
+    assert!(v.project_onto(axis) + v.reject_from(axis) == v);
+}
+
+#[test]
+pub fn test_project_reject_3d() {
+    let v = Point3D::new(1.0f32, 2.0, 3.0);
+    let axis = Point3D::new(1.0, 1.0, 0.0);
+    let rejection = v.reject_from(axis);
+    assert!(rejection.dot(axis).approx_eq(&0.0));
+
+    let sum = v.project_onto(axis) + rejection;
+    assert!(sum.x.approx_eq(&v.x) && sum.y.approx_eq(&v.y) && sum.z.approx_eq(&v.z));
+}
+
+#[test]
+pub fn test_min_max_abs_2d() {
+    let p1 = Point2D::new(1.0, -3.0);
+    let p2 = Point2D::new(-2.0, 4.0);
+    assert!(p1.min(p2) == Point2D::new(-2.0, -3.0));
+    assert!(p1.max(p2) == Point2D::new(1.0, 4.0));
+    assert!(p2.abs() == Point2D::new(2.0, 4.0));
+}
+
+#[test]
+pub fn test_min_max_abs_3d() {
+    let p1 = Point3D::new(1, -3, 5);
+    let p2 = Point3D::new(-2, 4, 5);
+    assert!(p1.min(p2) == Point3D::new(-2, -3, 5));
+    assert!(p1.max(p2) == Point3D::new(1, 4, 5));
+    assert!(p1.abs() == Point3D::new(1, 3, 5));
+}
+
+#[test]
+pub fn test_min_max_abs_4d() {
+    let p1 = Point4D::new(1.0, -3.0, 0.5, -1.0);
+    let p2 = Point4D::new(-2.0, 4.0, 0.25, 1.0);
+    assert!(p1.min(p2) == Point4D::new(-2.0, -3.0, 0.25, -1.0));
+    assert!(p1.max(p2) == Point4D::new(1.0, 4.0, 0.5, 1.0));
+    assert!(p1.abs() == Point4D::new(1.0, 3.0, 0.5, 1.0));
+}
+
+#[test]
+pub fn test_normalize_or() {
+    let fallback = Point2D::new(1.0f32, 0.0);
+
+    let n = Point2D::new(3.0f32, -4.0).normalize_or(fallback);
+    assert!(n.x.approx_eq(&0.6) && n.y.approx_eq(&-0.8));
+    assert!(n.length().approx_eq(&1.0));
+
+    assert!(Point2D::new(0.0f32, 0.0).normalize_or(fallback) == fallback);
+    assert!(Point2D::new(0.0f64, 0.0).try_normalize().is_none());
+}
+
+#[test]
+pub fn test_is_zero() {
+    assert!(Point2D::new(0, 0).is_zero());
+    assert!(Point3D::new(0.0, 0.0, 0.0).is_zero());
+
+    let tiny2 = Point2D::new(1.0e-9f32, 0.0);
+    assert!(!tiny2.is_zero());
+    assert!(tiny2.approx_is_zero(1.0e-6));
+
+    let tiny3 = Point3D::new(0.0f64, -1.0e-9, 1.0e-9);
+    assert!(!tiny3.is_zero());
+    assert!(tiny3.approx_is_zero(1.0e-6));
+    assert!(!Point3D::new(0.0f64, 0.1, 0.0).approx_is_zero(1.0e-6));
+}
+
+#[test]
+pub fn test_sum() {
+    let points = [Point2D::new(0.0, 0.0), Point2D::new(4.0, 0.0), Point2D::new(4.0, 2.0),
+                  Point2D::new(0.0, 2.0)];
+    let centroid = points.iter().cloned().sum::<Point2D<f32>>() / points.len() as f32;
+    assert!(centroid == Point2D::new(2.0, 1.0));
+
+    let points = vec![Point3D::new(1, 2, 3), Point3D::new(-1, 0, 3), Point3D::new(3, 4, 3)];
+    assert!(points.into_iter().sum::<Point3D<i32>>() == Point3D::new(3, 6, 9));
+}
+
+#[test]
+pub fn test_angle_between() {
+    use std::f32::consts::FRAC_PI_2;
+
+    let x = Point3D::new(2.0f32, 0.0, 0.0);
+    assert!(x.angle_between(Point3D::new(0.0, 0.0, 3.0)).approx_eq(&FRAC_PI_2));
+    assert!(x.angle_between(Point3D::new(5.0, 0.0, 0.0)) == 0.0);
+
+    // acos of the normalized dot product would round this to zero in f32.
+    let nearly_x = Point3D::new(1.0f32, 1.0e-4, 0.0);
+    assert!(x.angle_between(nearly_x).approx_eq_eps(&1.0e-4, &1.0e-8));
+
+    let y = Point3D::new(0.0f64, 1.0, 0.0);
+    assert!(y.angle_between(Point3D::new(0.0, -1.0, 0.0)).approx_eq(&::std::f64::consts::PI));
+}
+
+#[test]
+pub fn test_snap_to_grid() {
+    assert!(Point2D::new(14, 16).snap_to_grid(10) == Some(Point2D::new(10, 20)));
+    assert!(Point2D::new(-14, -16).snap_to_grid(10) == Some(Point2D::new(-10, -20)));
+    assert!(Point2D::new(5, -5).snap_to_grid(10) == Some(Point2D::new(10, -10)));
+    assert!(Point2D::new(23.7f32, -0.2).snap_to_grid(10.0) == Some(Point2D::new(20.0, 0.0)));
+
+    assert!(Point2D::new(3, 4).snap_to_grid(0).is_none());
+    assert!(Point2D::new(3.0f32, 4.0).snap_to_grid(0.0).is_none());
+    assert!(Point2D::new(126i8, 0).snap_to_grid(10).is_none());
+
+    // Integers are snapped exactly, even beyond the 2^53 that f64 holds exactly.
+    let big = (1i64 << 53) + 1;
+    assert!(Point2D::new(big, 7).snap_to_grid(1) == Some(Point2D::new(big, 7)));
+    assert!(Point2D::new(big + 4, 0).snap_to_grid(10) == Some(Point2D::new(big + 7, 0)));
+    assert!(Point2D::new(i64::MIN, 0).snap_to_grid(-1) == Some(Point2D::new(i64::MIN, 0)));
+    assert!(Point2D::new(i64::MAX, 0).snap_to_grid(10).is_none());
+    assert!(Point2D::new(15u32, 14).snap_to_grid(10) == Some(Point2D::new(20, 10)));
+    assert!(Point2D::new(-15, 15).snap_to_grid(-10) == Some(Point2D::new(-20, 20)));
+}
+
+#[test]
+pub fn test_normalize_homogeneous() {
+    let p = Point4D::new(2.0, -8.0, 6.0, 4.0).normalize_homogeneous();
+    assert!(p == Point4D::new(0.5, -2.0, 1.5, 1.0));
+
+    let at_infinity = Point4D::new(1.0, 2.0, 3.0, 0.0);
+    assert!(at_infinity.normalize_homogeneous() == at_infinity);
+}
+
+#[test]
+pub fn test_hashable_points() {
+    use std::collections::HashSet;
+    use std::f32;
+
+    let vertices = [Point3D::new(0.0, 1.0, 2.0), Point3D::new(-0.0, 1.0, 2.0),
+                    Point3D::new(0.0, 1.0, 2.0000002), Point3D::new(f32::NAN, 0.0, 0.0),
+                    Point3D::new(-f32::NAN, 0.0, 0.0), Point3D::new(0.0, 1.0, 2.0)];
+    let unique: HashSet<HashablePoint3D> = vertices.iter().map(|&v| HashablePoint3D(v)).collect();
+    assert!(unique.len() == 3);
+
+    let mut points = HashSet::new();
+    assert!(points.insert(HashablePoint2D(Point2D::new(1.5, -0.0))));
+    assert!(!points.insert(HashablePoint2D(Point2D::new(1.5, 0.0))));
+}
+
+#[test]
+pub fn test_rem() {
+    assert!(Point2D::new(17, 23) % 5 == Point2D::new(2, 3));
+    assert!(Point2D::new(17, 23) % Point2D::new(4, 10) == Point2D::new(1, 3));
+    assert!(Point2D::new(-7, 7) % 3 == Point2D::new(-1, 1));
+}
+
+#[test]
+pub fn test_spherical() {
+    use std::f32::consts::FRAC_PI_2;
+
+    let (r, theta, phi) = Point3D::new(0.0f32, 2.0, 0.0).to_spherical();
+    assert!(r.approx_eq(&2.0) && theta.approx_eq(&FRAC_PI_2) && phi.approx_eq(&FRAC_PI_2));
+
+    let (r, theta, _) = Point3D::new(0.0f32, 0.0, -3.0).to_spherical();
+    assert!(r.approx_eq(&3.0) && theta.approx_eq(&::std::f32::consts::PI));
+
+    let p = Point3D::new(1.5f64, -2.0, 0.75);
+    let (r, theta, phi) = p.to_spherical();
+    let q = Point3D::from_spherical(r, theta, phi);
+    assert!(q.x.approx_eq(&p.x) && q.y.approx_eq(&p.y) && q.z.approx_eq(&p.z));
+}
+
+#[test]
+pub fn test_rotate_90() {
+    let p = Point2D::new(3, -7);
+    assert!(Point2D::new(1, 0).rotate_90_cw() == Point2D::new(0, 1));
+    assert!(p.rotate_90_cw() == Point2D::new(7, 3));
+    assert!(p.rotate_180() == Point2D::new(-3, 7));
+    assert!(p.rotate_270_cw() == Point2D::new(-7, -3));
+    assert!(p.rotate_90_cw().rotate_90_cw() == p.rotate_180());
+    assert!(p.rotate_90_cw().rotate_270_cw() == p);
+}
+
+#[test]
+pub fn test_zero_trait() {
+    fn total<T: Zero + Add<T, Output=T> + Copy>(items: &[T]) -> T {
+        items.iter().fold(Zero::zero(), |sum, &item| sum + item)
+    }
+
+    let points = [Point2D::new(1, 2), Point2D::new(3, 4), Point2D::new(-1, 10)];
+    assert!(total(&points) == Point2D::new(3, 16));
+
+    let points = [Point3D::new(1.0, 2.0, 3.0), Point3D::new(0.5, 0.5, 0.5)];
+    assert!(total(&points) == Point3D::new(1.5, 2.5, 3.5));
+
+    let zero: Point4D<i32> = Zero::zero();
+    assert!(zero == Point4D::new(0, 0, 0, 0));
+}
+
+#[test]
+pub fn test_cross_normalized() {
+    let a = Point3D::new(2.0f32, 0.5, -1.0);
+    let b = Point3D::new(0.3f32, -4.0, 1.5);
+    let n = a.cross_normalized(b);
+
+    assert!(n.length().approx_eq(&1.0));
+    assert!(n.dot(a).approx_eq_eps(&0.0, &1.0e-5));
+    assert!(n.dot(b).approx_eq_eps(&0.0, &1.0e-5));
+    assert!(a.try_cross_normalized(b) == Some(n));
+
+    assert!(a.try_cross_normalized(a * 3.0).is_none());
+}
+
+#[test]
+pub fn test_to_vector() {
+    let p = Point2D::new(3.0, -4.0);
+    assert!(p.to_vector() == p);
+    assert!(Point2D::new(1, 2) + Size2D::new(10, 20).to_vector() == Point2D::new(11, 22));
+}
+
+#[test]
+pub fn test_closest_on_segment() {
+    let (a, b) = (Point2D::new(0.0f32, 0.0), Point2D::new(10.0, 0.0));
+
+    assert!(Point2D::new(-5.0, 3.0).closest_on_segment(a, b) == a);
+    assert!(Point2D::new(4.0, 3.0).closest_on_segment(a, b) == Point2D::new(4.0, 0.0));
+    assert!(Point2D::new(7.0, 0.0).closest_on_segment(a, b) == Point2D::new(7.0, 0.0));
+    assert!(Point2D::new(15.0, -2.0).closest_on_segment(a, b) == b);
+
+    let c = Point2D::new(1.0, 1.0);
+    assert!(Point2D::new(4.0, 5.0).closest_on_segment(c, c) == c);
+}
+
+#[test]
+pub fn test_lerp_clamped() {
+    let (a, b) = (Point2D::new(0.0, 10.0), Point2D::new(4.0, 20.0));
+    assert!(a.lerp(b, -0.5) == Point2D::new(-2.0, 5.0));
+    assert!(a.lerp_clamped(b, -0.5) == a);
+    assert!(a.lerp_clamped(b, 0.5) == Point2D::new(2.0, 15.0));
+    assert!(a.lerp_clamped(b, 1.5) == b);
+
+    let (a, b) = (Point3D::new(1.0, 1.0, 1.0), Point3D::new(3.0, -1.0, 1.0));
+    assert!(a.lerp(b, 1.5) == Point3D::new(4.0, -2.0, 1.0));
+    assert!(a.lerp_clamped(b, -0.5) == a);
+    assert!(a.lerp_clamped(b, 0.5) == Point3D::new(2.0, 0.0, 1.0));
+    assert!(a.lerp_clamped(b, 1.5) == b);
+}
+
+#[test]
+pub fn test_components() {
+    let v: Vec<i32> = Point3D::new(1, 2, 3).into_iter().collect();
+    assert!(v == vec![1, 2, 3]);
+
+    let p = Point2D::new(4, 5);
+    assert!(p.components().collect::<Vec<_>>() == vec![4, 5]);
+    assert!(p.components().sum::<i32>() == 9);
+}
+
+#[test]
+pub fn test_barycentric() {
+    let (a, b, c) = (Point2D::new(0.0, 0.0), Point2D::new(3.0, 0.0), Point2D::new(0.0, 3.0));
+    assert!(Point2D::barycentric(a, a, b, c) == (1.0, 0.0, 0.0));
+    assert!(Point2D::barycentric(b, a, b, c) == (0.0, 1.0, 0.0));
+    assert!(Point2D::barycentric(c, a, b, c) == (0.0, 0.0, 1.0));
+
+    let (u, v, w) = Point2D::barycentric(Point2D::new(1.0, 1.0), a, b, c);
+    assert!(u.approx_eq(&(1.0 / 3.0)) && v.approx_eq(&(1.0 / 3.0)) && w.approx_eq(&(1.0 / 3.0)));
+
+    // Outside the triangle one of the weights goes negative.
+    let (u, _, _) = Point2D::barycentric(Point2D::new(3.0, 3.0), a, b, c);
+    assert!(u < 0.0);
+
+    let (u, v, w) = Point2D::barycentric(a, a, b, Point2D::new(6.0, 0.0));
+    assert!(u.is_nan() && v.is_nan() && w.is_nan());
+}
+
+#[test]
+pub fn test_project_to_plane() {
+    let p = Point3D::new(1.0, 2.0, 3.0);
+    let xy = p.project_to_plane(Point3D::new(0.0, 0.0, 2.0), Point3D::new(5.0, 5.0, 0.0));
+    assert!(xy == Point3D::new(1.0, 2.0, 0.0));
+
+    // A plane tilted against the axes, x + y = 2.
+    let q = Point3D::new(2.0, 2.0, 7.0)
+        .project_to_plane(Point3D::new(1.0, 1.0, 0.0), Point3D::new(2.0, 0.0, 0.0));
+    assert!(q == Point3D::new(1.0, 1.0, 7.0));
+}
+
+#[test]
+pub fn test_interpolate_with() {
+    let (a, b) = (Point2D::new(0.0, 0.0), Point2D::new(8.0, -4.0));
+    assert!(a.interpolate_with(b, 0.25, |t| t) == a.lerp(b, 0.25));
+    assert!(a.interpolate_with(b, 0.5, |t| t * t) == Point2D::new(2.0, -1.0));
+}
+
+#[test]
+pub fn test_total_cmp() {
+    let nan = ::std::f32::NAN;
+    let mut points = [Point2D::new(1.0, nan), Point2D::new(nan, 0.0),
+                      Point2D::new(1.0, -2.0), Point2D::new(-3.0, 5.0)];
+    points.sort_by(|a, b| a.total_cmp(b));
+    assert!(points[0] == Point2D::new(-3.0, 5.0));
+    assert!(points[1] == Point2D::new(1.0, -2.0));
+    assert!(points[2].x == 1.0 && points[2].y.is_nan());
+    assert!(points[3].x.is_nan() && points[3].y == 0.0);
+
+    let a = Point3D::new(0.0, 1.0, nan);
+    assert!(a.total_cmp(&a) == Ordering::Equal);
+    assert!(a.total_cmp(&Point3D::new(0.0, 1.0, 2.0)) == Ordering::Greater);
+    assert!(Point3D::new(-0.0, 0.0, 0.0).total_cmp(&Point3D::new(0.0, -1.0, 0.0)) ==
+            Ordering::Less);
+}
+
+#[test]
+pub fn test_xyz() {
+    assert!(Point4D::new(2.0, 4.0, 6.0, 2.0).xyz() == Point3D::new(2.0, 4.0, 6.0));
+    assert!(Point4D::new(2.0, 4.0, 6.0, 2.0).normalize_homogeneous().xyz() ==
+            Point3D::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+pub fn test_splat() {
+    assert!(Point2D::splat(1) == Point2D::new(1, 1));
+    assert!(Point3D::splat(2.0) == Point3D::new(2.0, 2.0, 2.0));
+    assert!(Point4D::splat(-1.5) == Point4D::new(-1.5, -1.5, -1.5, -1.5));
+}
+
+#[test]
+pub fn test_signed_parallelogram_area() {
+    let (a, b) = (Point2D::new(3.0, 1.0), Point2D::new(1.0, 2.0));
+    assert!(signed_parallelogram_area(a, b) == a.cross(b));
+    assert!(signed_parallelogram_area(a, b) == 5.0);
+    assert!(signed_parallelogram_area(b, a) == -5.0);
+    assert!(signed_parallelogram_area(a, a * 2.0) == 0.0);
+}
+
+#[test]
+pub fn test_rounding_4d() {
+    let p = Point4D::new(1.5f32, -0.25, 2.75, -1.5);
+    assert!(p.floor() == Point4D::new(1.0, -1.0, 2.0, -2.0));
+    assert!(p.ceil() == Point4D::new(2.0, -0.0, 3.0, -1.0));
+    assert!(p.round() == Point4D::new(2.0, -0.0, 3.0, -2.0));
+    assert!(p.abs() == Point4D::new(1.5, 0.25, 2.75, 1.5));
+
+    let p = Point4D::new(0.5f64, -3.1, 7.9, 1.0);
+    assert!(p.floor() == Point4D::new(0.0, -4.0, 7.0, 1.0));
+    assert!(p.ceil() == Point4D::new(1.0, -3.0, 8.0, 1.0));
+    assert!(p.round() == Point4D::new(1.0, -3.0, 8.0, 1.0));
+}
+
+#[test]
+pub fn test_rotate_toward() {
+    use std::f32::consts::FRAC_PI_2;
+
+    let (right, up) = (Point2D::new(1.0, 0.0), Point2D::new(0.0, 1.0));
+    assert!(right.rotate_toward(up, 2.0) == up);
+
+    let step = right.rotate_toward(up, FRAC_PI_2 / 3.0);
+    let expected = Point2D::new((FRAC_PI_2 / 3.0).cos(), (FRAC_PI_2 / 3.0).sin());
+    assert!((step - expected).approx_is_zero(1e-6));
+
+    // Rotates clockwise when that is shorter.
+    let down = Point2D::new(0.0, -1.0);
+    assert!(right.rotate_toward(down, 0.1).y < 0.0);
+
+    let mut dir = right;
+    for _ in 0..3 {
+        dir = dir.rotate_toward(up, 0.6);
+    }
+    assert!(dir == up);
+}
+
+#[test]
+pub fn test_scale_around() {
+    let pivot = Point2D::new(1.0, 1.0);
+    let p = Point2D::new(3.0, 0.0);
+    assert!(p.scale_around(pivot, 1.0) == p);
+    assert!(p.scale_around(pivot, 2.0) == Point2D::new(5.0, -1.0));
+    assert!(pivot.scale_around(pivot, 10.0) == pivot);
+
+    let p = Point3D::new(1, 2, 3);
+    assert!(p.scale_around(Point3D::new(0, 2, 1), 2) == Point3D::new(2, 2, 5));
+}
+
+#[test]
+pub fn test_is_convex() {
+    let square = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 0.0),
+                  Point2D::new(1.0, 1.0), Point2D::new(0.0, 1.0)];
+    assert!(is_convex(&square));
+    let mut clockwise = square;
+    clockwise.reverse();
+    assert!(is_convex(&clockwise));
+
+    let triangle = [Point2D::new(0.0, 0.0), Point2D::new(4.0, 0.0), Point2D::new(1.0, 3.0)];
+    assert!(is_convex(&triangle));
+
+    let arrow = [Point2D::new(0.0, 0.0), Point2D::new(2.0, 1.0),
+                 Point2D::new(0.0, 2.0), Point2D::new(1.0, 1.0)];
+    assert!(!is_convex(&arrow));
+
+    let with_midpoint = [Point2D::new(0.0, 0.0), Point2D::new(0.5, 0.0), Point2D::new(1.0, 0.0),
+                         Point2D::new(1.0, 1.0), Point2D::new(0.0, 1.0)];
+    assert!(is_convex(&with_midpoint));
+
+    assert!(!is_convex(&square[..2]));
+    assert!(!is_convex(&[Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0)]));
+}
+
+#[test]
+pub fn test_array_4d() {
+    let p = Point4D::new(1.0, -2.0, 3.5, 1.0);
+    assert!(p.to_array() == [1.0, -2.0, 3.5, 1.0]);
+    assert!(Point4D::from_array(p.to_array()) == p);
+}
+
+#[cfg(feature = "plugins")]
+#[test]
+pub fn test_serde_4d() {
+    let p = Point4D::new(1.0f32, -2.0, 3.5, 0.25);
+    let json = ::serde_json::to_string(&p).unwrap();
+    let back: Point4D<f32> = ::serde_json::from_str(&json).unwrap();
+    assert!(back == p);
+}
+
+#[test]
+pub fn test_length_typed() {
+    #[derive(Debug, Copy, Clone)]
+    enum Mm {}
+
+    let p: TypedPoint2D<Mm, f32> = Point2D::typed(3.0, 4.0);
+    let length: Length<Mm, f32> = p.length_typed();
+    assert!(length.get() == 5.0);
+
+    let p: Point3D<Length<Mm, f32>> = Point3D::new(Length::new(2.0), Length::new(3.0),
+                                                   Length::new(6.0));
+    assert!(p.length_typed().get() == 7.0);
+
+    // Typed and untyped lengths share one formula, so they agree exactly.
+    let p: TypedPoint2D<Mm, f32> = Point2D::typed(0.1, 0.7);
+    assert!(p.length_typed().get() == p.to_untyped().length());
+}
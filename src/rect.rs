@@ -8,7 +8,8 @@
 // except according to those terms.
 
 use length::Length;
-use num::Zero;
+use matrix::Matrix4;
+use num::{Int, One, Zero};
 use point::Point2D;
 use size::Size2D;
 
@@ -110,6 +111,44 @@ impl<T: Copy + Clone + PartialOrd + Add<T, Output=T> + Sub<T, Output=T> + Zero>
         }
     }
 
+    /// Returns the area covered by this rectangle but not by `other`, as up to four
+    /// non-overlapping rectangles.
+    ///
+    /// The pieces are the full-width bands above and below `other`, then the parts to the
+    /// left and right of it. No rectangle is returned if `other` covers this one entirely, and
+    /// this one is returned whole if `other` has no area.
+    pub fn subtract(&self, other: &Rect<T>) -> Vec<Rect<T>> {
+        if self.size.width <= Zero::zero() || self.size.height <= Zero::zero() {
+            return vec![];
+        }
+        if other.size.width <= Zero::zero() || other.size.height <= Zero::zero() {
+            return vec![*self];
+        }
+        let clip = match self.intersection(other) {
+            Some(clip) => clip,
+            None => return vec![*self],
+        };
+
+        let mut result = Vec::with_capacity(4);
+        if clip.min_y() > self.min_y() {
+            result.push(Rect::new(self.origin,
+                                  Size2D::new(self.size.width, clip.min_y() - self.min_y())));
+        }
+        if clip.max_y() < self.max_y() {
+            result.push(Rect::new(Point2D::new(self.min_x(), clip.max_y()),
+                                  Size2D::new(self.size.width, self.max_y() - clip.max_y())));
+        }
+        if clip.min_x() > self.min_x() {
+            result.push(Rect::new(Point2D::new(self.min_x(), clip.min_y()),
+                                  Size2D::new(clip.min_x() - self.min_x(), clip.size.height)));
+        }
+        if clip.max_x() < self.max_x() {
+            result.push(Rect::new(Point2D::new(clip.max_x(), clip.min_y()),
+                                  Size2D::new(self.max_x() - clip.max_x(), clip.size.height)));
+        }
+        result
+    }
+
     #[inline]
     pub fn translate(&self, other: &Point2D<T>) -> Rect<T> {
         Rect {
@@ -124,6 +163,13 @@ impl<T: Copy + Clone + PartialOrd + Add<T, Output=T> + Sub<T, Output=T> + Zero>
         self.origin.y <= other.y && other.y < self.origin.y + self.size.height
     }
 
+    /// Returns true if `contains` holds for every point, stopping at the first one outside.
+    /// An empty set of points is trivially contained.
+    #[inline]
+    pub fn contains_all<I: IntoIterator<Item=Point2D<T>>>(&self, points: I) -> bool {
+        points.into_iter().all(|p| self.contains(&p))
+    }
+
     #[inline]
     pub fn inflate(&self, width: T, height: T) -> Rect<T> {
         Rect {
@@ -147,6 +193,29 @@ impl<T: Copy + Clone + PartialOrd + Add<T, Output=T> + Sub<T, Output=T> + Zero>
         Point2D::new(self.max_x(), self.max_y())
     }
 
+    /// Returns the four corners of the rectangle, clockwise from the origin (in a y-down
+    /// coordinate system): top-left, top-right, bottom-right, bottom-left.
+    #[inline]
+    pub fn corners(&self) -> [Point2D<T>; 4] {
+        [self.origin, self.top_right(), self.bottom_right(), self.bottom_left()]
+    }
+
+    /// Applies `f` to each corner of the rectangle and returns the bounding rectangle of the
+    /// results. For non-linear `f` this only bounds the corners, not every warped edge.
+    pub fn bounding_box_of<F: Fn(Point2D<T>) -> Point2D<T>>(&self, f: F) -> Rect<T> {
+        let corners = self.corners();
+        let first = f(corners[0]);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (first.x, first.y, first.x, first.y);
+        for corner in corners[1..].iter() {
+            let p = f(*corner);
+            min_x = min(min_x, p.x);
+            min_y = min(min_y, p.y);
+            max_x = max(max_x, p.x);
+            max_y = max(max_y, p.y);
+        }
+        Rect::new(Point2D::new(min_x, min_y), Size2D::new(max_x - min_x, max_y - min_y))
+    }
+
     #[inline]
     pub fn translate_by_size(&self, size: &Size2D<T>) -> Rect<T> {
         Rect::new(Point2D::new(self.origin.x + size.width, self.origin.y + size.height),
@@ -154,6 +223,112 @@ impl<T: Copy + Clone + PartialOrd + Add<T, Output=T> + Sub<T, Output=T> + Zero>
     }
 }
 
+impl<T: Int> Rect<T> {
+    /// Returns an iterator over the integer lattice points covered by this rectangle, in
+    /// row-major order.
+    ///
+    /// Like `contains`, the area is half-open: points on the top and left edges are yielded,
+    /// points on the bottom and right edges are not.
+    #[inline]
+    pub fn points(&self) -> RectPoints<T> {
+        RectPoints {
+            rect: *self,
+            next: self.origin,
+        }
+    }
+}
+
+/// An iterator over the integer lattice points of a rectangle. See `Rect::points`.
+pub struct RectPoints<T> {
+    rect: Rect<T>,
+    next: Point2D<T>,
+}
+
+impl<T: Int> Iterator for RectPoints<T> {
+    type Item = Point2D<T>;
+
+    fn next(&mut self) -> Option<Point2D<T>> {
+        if self.next.y >= self.rect.max_y() || self.next.x >= self.rect.max_x() {
+            return None;
+        }
+
+        let point = self.next;
+        self.next.x = self.next.x + One::one();
+        if self.next.x >= self.rect.max_x() {
+            self.next.x = self.rect.min_x();
+            self.next.y = self.next.y + One::one();
+        }
+        Some(point)
+    }
+}
+
+impl<T: Copy + Clone + Add<T, Output=T> + Sub<T, Output=T> + Mul<T, Output=T>> Rect<T> {
+    /// Returns this rectangle moved inside `container` according to the anchors `ax` and `ay`,
+    /// which are normally in `[0, 1]`: 0 aligns the left/top edges, 1 the right/bottom edges,
+    /// and `(0.5, 0.5)` centers it. The size is unchanged.
+    #[inline]
+    pub fn align_in(&self, container: &Rect<T>, ax: T, ay: T) -> Rect<T> {
+        let x = container.origin.x + (container.size.width - self.size.width) * ax;
+        let y = container.origin.y + (container.size.height - self.size.height) * ay;
+        Rect::new(Point2D::new(x, y), self.size)
+    }
+}
+
+impl<T: Copy + Clone + PartialOrd + Add<T, Output=T> + Sub<T, Output=T> +
+        Mul<T, Output=T> + Zero> Rect<T> {
+    /// Returns the area of the overlap between this rectangle and `other`, or zero if they
+    /// don't intersect. Equivalent to the area of `intersection`, without building the rect.
+    #[inline]
+    pub fn intersection_area(&self, other: &Rect<T>) -> T {
+        if !self.intersects(other) {
+            return Zero::zero();
+        }
+        let width = min(self.max_x(), other.max_x()) - max(self.min_x(), other.min_x());
+        let height = min(self.max_y(), other.max_y()) - max(self.min_y(), other.min_y());
+        width * height
+    }
+}
+
+impl<T: Clone> Rect<T> {
+    /// Returns the size as a vector from the origin to the bottom-right corner.
+    #[inline]
+    pub fn size_as_vector(&self) -> Point2D<T> {
+        self.size.to_vector()
+    }
+
+    /// Returns the vector from the top-left corner to the bottom-right one, `max - min`. This
+    /// is the same as `size_as_vector`.
+    #[inline]
+    pub fn diagonal(&self) -> Point2D<T> {
+        self.size_as_vector()
+    }
+}
+
+impl<T: Copy + PartialOrd + Add<T, Output=T> + Div<T, Output=T> + One> Rect<T> {
+    /// Returns the radius of the largest circle that fits inside this rectangle, which is half
+    /// its smaller dimension.
+    #[inline]
+    pub fn inscribed_radius(&self) -> T {
+        let two = T::one() + T::one();
+        min(self.size.width, self.size.height) / two
+    }
+}
+
+impl<T: Copy + NumCast> Rect<T> {
+    /// Returns the length of `diagonal`.
+    pub fn diagonal_length(&self) -> f32 {
+        let width: f32 = NumCast::from(self.size.width).unwrap();
+        let height: f32 = NumCast::from(self.size.height).unwrap();
+        width.hypot(height)
+    }
+
+    /// Returns the radius of the smallest circle around this rectangle's center that contains
+    /// it, which is half its diagonal.
+    pub fn circumscribed_radius(&self) -> f32 {
+        self.diagonal_length() / 2.0
+    }
+}
+
 impl<T> Rect<T> {
     #[inline]
     pub fn scale<Scale: Copy>(&self, x: Scale, y: Scale) -> Rect<T>
@@ -165,6 +340,55 @@ impl<T> Rect<T> {
     }
 }
 
+impl Rect<f32> {
+    /// Returns the Euclidean distance from `p` to the nearest point of this rectangle, which
+    /// is zero for points inside it or on its edges.
+    pub fn distance_to_point(&self, p: &Point2D<f32>) -> f32 {
+        let nearest = Point2D::new(max(self.min_x(), min(p.x, self.max_x())),
+                                   max(self.min_y(), min(p.y, self.max_y())));
+        (*p - nearest).length()
+    }
+
+    /// Returns this rectangle transformed by `m`, if `m` only scales and translates in 2d.
+    ///
+    /// In that case the result is exactly a rectangle. If `m` has any rotation, skew or
+    /// perspective, `None` is returned and callers should fall back to a bounding box such as
+    /// `Matrix2D::transform_rect` computes.
+    pub fn axis_aligned_transform(&self, m: &Matrix4) -> Option<Rect<f32>> {
+        if m.m12 != 0.0 || m.m21 != 0.0 || m.m14 != 0.0 || m.m24 != 0.0 || m.m44 != 1.0 {
+            return None;
+        }
+
+        let a = m.transform_point(&self.origin);
+        let b = m.transform_point(&self.bottom_right());
+        let origin = Point2D::new(min(a.x, b.x), min(a.y, b.y));
+        Some(Rect::new(origin, Size2D::new(max(a.x, b.x) - origin.x, max(a.y, b.y) - origin.y)))
+    }
+
+    /// Returns this rectangle with the origin and size components each rounded to the nearest
+    /// integer (halfway cases away from zero).
+    ///
+    /// Since the size is rounded independently of the origin, the max edges can differ from
+    /// rounding the bottom-right corner: `x = 0.4, width = 0.4` rounds to an empty rect
+    /// although `max_x` is at 0.8.
+    pub fn round(&self) -> Rect<f32> {
+        Rect::new(Point2D::new(self.origin.x.round(), self.origin.y.round()),
+                  Size2D::new(self.size.width.round(), self.size.height.round()))
+    }
+
+    /// Cast to another numeric type, rounding the origin and size components to the nearest
+    /// integer first rather than truncating, which would shrink the rectangle. Returns `None`
+    /// if a rounded value doesn't fit in `T`.
+    pub fn round_cast<T: NumCast + Clone>(&self) -> Option<Rect<T>> {
+        let x = NumCast::from(self.origin.x.round());
+        let y = NumCast::from(self.origin.y.round());
+        match (x, y, self.size.round_cast()) {
+            (Some(x), Some(y), Some(size)) => Some(Rect::new(Point2D::new(x, y), size)),
+            _ => None
+        }
+    }
+}
+
 impl<T: PartialEq + Zero> Rect<T> {
     pub fn zero() -> Rect<T> {
         Rect {
@@ -406,3 +630,207 @@ fn test_is_empty() {
     assert!(Rect::new(Point2D::new(10u32, 10u32), Size2D::new(0u32, 10u32)).is_empty());
     assert!(!Rect::new(Point2D::new(10u32, 10u32), Size2D::new(1u32, 1u32)).is_empty());
 }
+
+#[test]
+fn test_corners() {
+    let r = Rect::new(Point2D::new(-10, 5), Size2D::new(30, 20));
+    let corners = r.corners();
+
+    assert!(corners[0] == Point2D::new(-10, 5));
+    assert!(corners[1] == Point2D::new(20, 5));
+    assert!(corners[2] == Point2D::new(20, 25));
+    assert!(corners[3] == Point2D::new(-10, 25));
+}
+
+#[test]
+fn test_points() {
+    let r = Rect::new(Point2D::new(1, 2), Size2D::new(3, 2));
+    let points: Vec<Point2D<i32>> = r.points().collect();
+
+    assert!(points.len() == 6);
+    assert!(points == vec![Point2D::new(1, 2), Point2D::new(2, 2), Point2D::new(3, 2),
+                           Point2D::new(1, 3), Point2D::new(2, 3), Point2D::new(3, 3)]);
+
+    let empty = Rect::new(Point2D::new(0usize, 0usize), Size2D::new(0usize, 4usize));
+    assert!(empty.points().next().is_none());
+}
+
+#[test]
+fn test_axis_aligned_transform() {
+    let r = Rect::new(Point2D::new(1.0, 2.0), Size2D::new(10.0, 20.0));
+
+    let m = Matrix4::create_scale(2.0, -0.5, 1.0).translate(5.0, 0.0, 0.0);
+    let transformed = r.axis_aligned_transform(&m);
+    assert!(transformed == Some(Rect::new(Point2D::new(12.0, -11.0), Size2D::new(20.0, 10.0))));
+
+    let rotation = Matrix4::create_rotation(0.0, 0.0, 1.0, 0.3);
+    assert!(r.axis_aligned_transform(&rotation).is_none());
+}
+
+#[test]
+fn test_subtract() {
+    fn total_area(rects: &[Rect<i32>]) -> i32 {
+        rects.iter().map(|r| r.size.area()).sum()
+    }
+
+    let r = Rect::new(Point2D::new(0, 0), Size2D::new(10, 10));
+
+    // Fully contained.
+    let covering = Rect::new(Point2D::new(-5, -5), Size2D::new(20, 20));
+    assert!(r.subtract(&covering).is_empty());
+
+    // Disjoint.
+    let disjoint = Rect::new(Point2D::new(20, 0), Size2D::new(5, 5));
+    assert!(r.subtract(&disjoint) == vec![r]);
+
+    // Subtracting an empty rect, even one inside, leaves the rect whole.
+    let empty = Rect::new(Point2D::new(5, 5), Size2D::new(0, 0));
+    assert!(r.subtract(&empty) == vec![r]);
+    let line = Rect::new(Point2D::new(0, 5), Size2D::new(10, 0));
+    assert!(r.subtract(&line) == vec![r]);
+
+    // A hole in the middle leaves four pieces.
+    let hole = Rect::new(Point2D::new(2, 3), Size2D::new(4, 5));
+    let pieces = r.subtract(&hole);
+    assert!(pieces.len() == 4);
+    assert!(total_area(&pieces) == 100 - 20);
+    for (i, a) in pieces.iter().enumerate() {
+        assert!(!a.intersects(&hole));
+        for b in pieces[i + 1..].iter() {
+            assert!(!a.intersects(b));
+        }
+    }
+
+    // Overlapping a corner leaves two pieces.
+    let corner = Rect::new(Point2D::new(5, 5), Size2D::new(10, 10));
+    let pieces = r.subtract(&corner);
+    assert!(pieces.len() == 2);
+    assert!(total_area(&pieces) == 100 - 25);
+
+    // Overlapping one edge fully leaves one piece.
+    let edge = Rect::new(Point2D::new(-1, 6), Size2D::new(12, 10));
+    assert!(r.subtract(&edge) == vec![Rect::new(Point2D::new(0, 0), Size2D::new(10, 6))]);
+}
+
+#[test]
+fn test_align_in() {
+    let container = Rect::new(Point2D::new(10.0, 20.0), Size2D::new(100.0, 50.0));
+    let r = Rect::new(Point2D::new(-3.0, 7.0), Size2D::new(20.0, 10.0));
+
+    let top_left = r.align_in(&container, 0.0, 0.0);
+    assert!(top_left == Rect::new(Point2D::new(10.0, 20.0), r.size));
+
+    let bottom_right = r.align_in(&container, 1.0, 1.0);
+    assert!(bottom_right == Rect::new(Point2D::new(90.0, 60.0), r.size));
+
+    let centered = r.align_in(&container, 0.5, 0.5);
+    assert!(centered == Rect::new(Point2D::new(50.0, 40.0), r.size));
+}
+
+#[test]
+fn test_bounding_box_of() {
+    let r = Rect::new(Point2D::new(1.0, -2.0), Size2D::new(4.0, 3.0));
+
+    let scaled = r.bounding_box_of(|p| Point2D::new(p.x * 3.0, p.y * -0.5));
+    assert!(scaled == Rect::new(Point2D::new(3.0, -0.5), Size2D::new(12.0, 1.5)));
+
+    let squared = r.bounding_box_of(|p| Point2D::new(p.x * p.x, p.y));
+    assert!(squared == Rect::new(Point2D::new(1.0, -2.0), Size2D::new(24.0, 3.0)));
+}
+
+#[test]
+fn test_distance_to_point() {
+    let r = Rect::new(Point2D::new(0.0, 0.0), Size2D::new(10.0, 5.0));
+
+    assert!(r.distance_to_point(&Point2D::new(3.0, 2.0)) == 0.0);
+    assert!(r.distance_to_point(&Point2D::new(10.0, 5.0)) == 0.0);
+    assert!(r.distance_to_point(&Point2D::new(4.0, -2.5)) == 2.5);
+    assert!(r.distance_to_point(&Point2D::new(13.0, 9.0)) == 5.0);
+}
+
+#[test]
+fn test_size_as_vector() {
+    let r = Rect::new(Point2D::new(5, 5), Size2D::new(30, 20));
+    assert!(r.size_as_vector() == Point2D::new(30, 20));
+    assert!(r.origin + r.size_as_vector() == r.bottom_right());
+}
+
+#[test]
+fn test_inscribed_circumscribed_radius() {
+    let r = Rect::new(Point2D::new(10, 20), Size2D::new(6, 8));
+    assert!(r.inscribed_radius() == 3);
+    assert!(r.circumscribed_radius() == 5.0);
+
+    let r = Rect::new(Point2D::new(0.0, 0.0), Size2D::new(3.0, 1.0));
+    assert!(r.inscribed_radius() == 0.5);
+}
+
+#[test]
+fn test_round_cast() {
+    let r: Rect<Length<(), f32>> = Rect::new(Point2D::new(Length::new(0.6), Length::new(1.2)),
+                                             Size2D::new(Length::new(9.7), Length::new(4.5)));
+    let truncated: Rect<Length<(), i32>> = r.cast().unwrap();
+    assert!((truncated.origin.x.get(), truncated.size.width.get()) == (0, 9));
+
+    let r = Rect::new(Point2D::new(0.6, 1.2), Size2D::new(9.7, 4.5));
+    let rounded: Rect<i32> = r.round_cast().unwrap();
+    assert!(rounded == Rect::new(Point2D::new(1, 1), Size2D::new(10, 5)));
+}
+
+#[test]
+fn test_intersection_area() {
+    let a = Rect::new(Point2D::new(0, 0), Size2D::new(10, 10));
+    let b = Rect::new(Point2D::new(6, -2), Size2D::new(10, 5));
+    assert!(a.intersection_area(&b) == 12);
+    assert!(b.intersection_area(&a) == 12);
+    assert!(a.intersection_area(&a) == 100);
+
+    let disjoint = Rect::new(Point2D::new(20, 20), Size2D::new(5, 5));
+    assert!(a.intersection_area(&disjoint) == 0);
+    // Touching edges don't overlap.
+    let adjacent = Rect::new(Point2D::new(10, 0), Size2D::new(5, 5));
+    assert!(a.intersection_area(&adjacent) == 0);
+}
+
+#[test]
+fn test_round() {
+    let r = Rect::new(Point2D::new(0.6, -1.5), Size2D::new(9.7, 4.2));
+    assert!(r.round() == Rect::new(Point2D::new(1.0, -2.0), Size2D::new(10.0, 4.0)));
+
+    let r = Rect::new(Point2D::new(0.4, 0.0), Size2D::new(0.4, 1.0));
+    assert!(r.round().max_x() == 0.0);
+    assert!(r.max_x().round() == 1.0);
+}
+
+#[cfg(feature = "plugins")]
+#[test]
+fn test_serde_round_trip() {
+    let r = Rect::new(Point2D::new(1.5, -2.0), Size2D::new(3.0, 4.25));
+    let json = ::serde_json::to_string(&r).unwrap();
+    let back: Rect<f32> = ::serde_json::from_str(&json).unwrap();
+    assert!(back == r);
+
+    let r: Rect<Length<(), i32>> = Rect::new(Point2D::new(Length::new(1), Length::new(2)),
+                                             Size2D::new(Length::new(3), Length::new(4)));
+    let json = ::serde_json::to_string(&r).unwrap();
+    let back: Rect<Length<(), i32>> = ::serde_json::from_str(&json).unwrap();
+    assert!(back == r);
+}
+
+#[test]
+fn test_diagonal() {
+    let r = Rect::new(Point2D::new(1, 2), Size2D::new(3, 4));
+    assert!(r.diagonal() == Point2D::new(3, 4));
+    assert!(r.diagonal_length() == 5.0);
+}
+
+#[test]
+fn test_contains_all() {
+    let r = Rect::new(Point2D::new(0, 0), Size2D::new(10, 10));
+    let inside = [Point2D::new(0, 0), Point2D::new(5, 9), Point2D::new(9, 3)];
+    assert!(r.contains_all(inside.iter().cloned()));
+    assert!(r.contains_all(Vec::new()));
+
+    let outlier = [Point2D::new(1, 1), Point2D::new(10, 5), Point2D::new(2, 2)];
+    assert!(!r.contains_all(outlier));
+}
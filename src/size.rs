@@ -8,11 +8,14 @@
 // except according to those terms.
 
 use length::Length;
-use num::Zero;
+use matrix::Matrix4;
+use num::{Float, Zero};
+use point::Point2D;
 
 use num_lib::NumCast;
+use std::cmp;
 use std::fmt::{self, Formatter};
-use std::ops::{Mul, Div};
+use std::ops::{Mul, Div, Neg};
 
 #[derive(Clone, Copy, RustcDecodable, RustcEncodable, PartialEq)]
 #[cfg_attr(feature = "plugins", derive(HeapSizeOf, Deserialize, Serialize))]
@@ -40,12 +43,92 @@ impl<T: Clone> Size2D<T> {
             height: height
         }
     }
+
+    /// Creates a size with both dimensions set to `v`.
+    #[inline]
+    pub fn splat(v: T) -> Size2D<T> {
+        Size2D::new(v.clone(), v)
+    }
+
+    /// Returns the size with width and height exchanged, as for content rotated by 90°.
+    #[inline]
+    pub fn swap(&self) -> Size2D<T> {
+        Size2D::new(self.height.clone(), self.width.clone())
+    }
+}
+
+impl<T: Clone> Size2D<T> {
+    /// Returns `(width, height)` as a displacement vector.
+    #[inline]
+    pub fn to_vector(&self) -> Point2D<T> {
+        Point2D::new(self.width.clone(), self.height.clone())
+    }
 }
 
 impl<T:Copy + Clone + Mul<T, Output=U>, U> Size2D<T> {
     pub fn area(&self) -> U { self.width * self.height }
 }
 
+impl<T: Copy + PartialOrd> Size2D<T> {
+    /// Clamps the width and height independently between those of `min` and `max`.
+    #[inline]
+    pub fn clamp(&self, min: Size2D<T>, max: Size2D<T>) -> Size2D<T> {
+        Size2D::new(clamp(self.width, min.width, max.width),
+                    clamp(self.height, min.height, max.height))
+    }
+}
+
+impl<T: Float> Size2D<T> {
+    /// Returns `width / height`. With a zero height this follows float division: infinite for
+    /// a non-zero width, NaN for an empty size.
+    #[inline]
+    pub fn aspect_ratio(&self) -> T {
+        self.width / self.height
+    }
+}
+
+impl<T: NumCast + Clone> Size2D<T> {
+    /// Returns the largest integer size with the same aspect ratio as this size that fits
+    /// within `bounds`.
+    ///
+    /// Both dimensions are rounded down, so the result never exceeds `bounds` and matches the
+    /// aspect ratio to within one pixel. An empty size always fits as `0×0`.
+    pub fn fit_within(&self, bounds: Size2D<u32>) -> Size2D<u32> {
+        let width: f64 = NumCast::from(self.width.clone()).unwrap();
+        let height: f64 = NumCast::from(self.height.clone()).unwrap();
+        if width <= 0.0 || height <= 0.0 {
+            return Size2D::new(0, 0);
+        }
+
+        let scale = (bounds.width as f64 / width).min(bounds.height as f64 / height);
+        Size2D::new(cmp::min((width * scale).floor() as u32, bounds.width),
+                    cmp::min((height * scale).floor() as u32, bounds.height))
+    }
+}
+
+impl Size2D<f32> {
+    /// Transforms `(width, height)` as a vector by the 2d linear part of `m`, ignoring any
+    /// translation.
+    ///
+    /// Under rotation or skew this is one edge vector of the transformed parallelogram, not
+    /// the size of its bounding box, and the components can become negative.
+    #[inline]
+    pub fn transform_size(&self, m: &Matrix4) -> Size2D<f32> {
+        Size2D::new(self.width * m.m11 + self.height * m.m21,
+                    self.width * m.m12 + self.height * m.m22)
+    }
+
+    /// Cast to another numeric type, rounding each dimension to the nearest integer first
+    /// (halfway cases away from zero) rather than truncating towards zero. Returns `None` if
+    /// a rounded value doesn't fit in `T`.
+    pub fn round_cast<T: NumCast + Clone>(&self) -> Option<Size2D<T>> {
+        match (NumCast::from(self.width.round()), NumCast::from(self.height.round())) {
+            (Some(w), Some(h)) => Some(Size2D::new(w, h)),
+            _ => None
+        }
+    }
+}
+
 impl<T: Zero> Size2D<T> {
     pub fn zero() -> Size2D<T> {
         Size2D {
@@ -80,6 +163,25 @@ impl<Scale: Copy, T0: Div<Scale, Output=T1>, T1: Clone> Div<Scale> for Size2D<T0
     }
 }
 
+#[inline]
+fn clamp<T: PartialOrd>(x: T, min: T, max: T) -> T {
+    if x < min {
+        min
+    } else if x > max {
+        max
+    } else {
+        x
+    }
+}
+
+impl<T: Clone + Neg<Output=T>> Neg for Size2D<T> {
+    type Output = Size2D<T>;
+    #[inline]
+    fn neg(self) -> Size2D<T> {
+        Size2D::new(-self.width, -self.height)
+    }
+}
+
 // Convenient aliases for Size2D with typed units
 
 pub type TypedSize2D<Unit, T> = Size2D<Length<Unit, T>>;
@@ -120,3 +222,84 @@ impl<Unit, T: NumCast + Clone> Size2D<Length<Unit, T>> {
         self.cast().unwrap()
     }
 }
+
+#[test]
+fn test_fit_within() {
+    let bounds = Size2D::new(1920u32, 1080u32);
+
+    let fitted = Size2D::new(4.0f32, 3.0).fit_within(bounds);
+    assert!(fitted == Size2D::new(1440, 1080));
+
+    let fitted = Size2D::new(1000u32, 100u32).fit_within(bounds);
+    assert!(fitted == Size2D::new(1920, 192));
+
+    let fitted = Size2D::new(333.0f64, 777.0).fit_within(Size2D::new(100, 100));
+    assert!(fitted.width <= 100 && fitted.height <= 100);
+    let aspect = 333.0 / 777.0;
+    assert!((fitted.width as f64 - fitted.height as f64 * aspect).abs() <= 1.0);
+
+    assert!(Size2D::new(0.0f32, 5.0).fit_within(bounds) == Size2D::new(0, 0));
+}
+
+#[test]
+fn test_clamp() {
+    let min = Size2D::new(10, 20);
+    let max = Size2D::new(100, 200);
+
+    assert!(Size2D::new(500, 300).clamp(min, max) == max);
+    assert!(Size2D::new(5, 50).clamp(min, max) == Size2D::new(10, 50));
+    assert!(Size2D::new(50, 50).clamp(min, max) == Size2D::new(50, 50));
+}
+
+#[test]
+fn test_neg() {
+    assert!(-Size2D::new(3, -4) == Size2D::new(-3, 4));
+    assert!(-Size2D::new(1.5, 0.0) == Size2D::new(-1.5, 0.0));
+}
+
+#[test]
+fn test_transform_size() {
+    let m = Matrix4::create_scale(2.0, 0.5, 1.0).translate(100.0, 100.0, 0.0);
+    assert!(Size2D::new(10.0, 20.0).transform_size(&m) == Size2D::new(20.0, 10.0));
+}
+
+#[test]
+fn test_aspect_ratio() {
+    assert!(Size2D::new(1920.0f32, 1080.0).aspect_ratio() == 16.0 / 9.0);
+    assert!(Size2D::new(4.0f64, 0.0).aspect_ratio().is_infinite());
+    assert!(Size2D::new(0.0f64, 0.0).aspect_ratio().is_nan());
+}
+
+#[test]
+fn test_to_vector() {
+    assert!(Size2D::new(3, 4).to_vector() == Point2D::new(3, 4));
+}
+
+#[test]
+fn test_round_cast() {
+    let size: Size2D<Length<(), f32>> = Size2D::new(Length::new(2.6), Length::new(3.4));
+    let truncated: Size2D<Length<(), i32>> = size.cast().unwrap();
+    assert!((truncated.width.get(), truncated.height.get()) == (2, 3));
+
+    let rounded: Size2D<i32> = Size2D::new(2.6, 3.4).round_cast().unwrap();
+    assert!(rounded == Size2D::new(3, 3));
+
+    assert!(Size2D::new(-0.5f32, 1e10).round_cast::<i32>() == None);
+}
+
+#[test]
+fn test_zero_splat() {
+    let size = Size2D::splat(5);
+    assert!(size.width == 5);
+    assert!(size.height == 5);
+
+    let zero: Size2D<f32> = Zero::zero();
+    assert!(zero == Size2D::zero());
+    assert!(zero == Size2D::splat(0.0));
+}
+
+#[test]
+fn test_swap() {
+    assert!(Size2D::new(1920, 1080).swap() == Size2D::new(1080, 1920));
+    assert!(Size2D::splat(3.0).swap() == Size2D::splat(3.0));
+}